@@ -0,0 +1,171 @@
+//! Response assertions
+
+use serde::{Deserialize, Serialize};
+use crate::collection::{query_json_path, response_header};
+use crate::http::HttpResponse;
+
+// Declarative check of a response, tagged by "type"
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ResponseAssertion {
+    StatusEquals {
+        status: u16,
+    },
+    StatusInRange {
+        min: u16,
+        max: u16, // inclusive
+    },
+    HeaderPresent {
+        name: String,
+    },
+    HeaderMatches {
+        name: String,
+        pattern: String, // regex
+    },
+    JsonPathEquals {
+        path: String,
+        value: serde_json::Value,
+    },
+    JsonPathExists {
+        path: String,
+    },
+    BodyMatches {
+        pattern: String, // regex
+    },
+    ResponseTimeBelow {
+        ms: f64,
+    },
+    JsonSchema {
+        schema: serde_json::Value,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AssertionResult {
+    assertion: ResponseAssertion,
+    pub(crate) passed: bool,
+    message: String,
+}
+
+// Numbers compare by value, so 1 equals 1.0
+pub(crate) fn json_values_equal(left: &serde_json::Value, right: &serde_json::Value) -> bool {
+    match (left, right) {
+        (serde_json::Value::Number(left), serde_json::Value::Number(right)) => left.as_f64() == right.as_f64(),
+        (serde_json::Value::Array(left), serde_json::Value::Array(right)) => {
+            left.len() == right.len() && left.iter().zip(right).all(|(left, right)| json_values_equal(left, right))
+        }
+        (serde_json::Value::Object(left), serde_json::Value::Object(right)) => {
+            left.len() == right.len()
+                && left
+                    .iter()
+                    .all(|(key, value)| right.get(key).is_some_and(|other| json_values_equal(value, other)))
+        }
+        _ => left == right,
+    }
+}
+
+fn check_assertion(assertion: &ResponseAssertion, response: &HttpResponse) -> Result<String, String> {
+    if let Some(error) = &response.error {
+        return Err(format!("No response: {}", error));
+    }
+    match assertion {
+        ResponseAssertion::StatusEquals { status } => {
+            if response.status == *status {
+                Ok(format!("Status is {}", status))
+            } else {
+                Err(format!("Expected status {}, got {}", status, response.status))
+            }
+        }
+        ResponseAssertion::StatusInRange { min, max } => {
+            if (*min..=*max).contains(&response.status) {
+                Ok(format!("Status {} is within {}-{}", response.status, min, max))
+            } else {
+                Err(format!("Expected status within {}-{}, got {}", min, max, response.status))
+            }
+        }
+        ResponseAssertion::HeaderPresent { name } => match response_header(response, name) {
+            Some(_) => Ok(format!("Header '{}' is present", name)),
+            None => Err(format!("Header '{}' is missing", name)),
+        },
+        ResponseAssertion::HeaderMatches { name, pattern } => {
+            let regex = regex::Regex::new(pattern).map_err(|e| format!("Invalid regex '{}': {}", pattern, e))?;
+            let value = response_header(response, name).ok_or_else(|| format!("Header '{}' is missing", name))?;
+            if regex.is_match(value) {
+                Ok(format!("Header '{}' matches '{}'", name, pattern))
+            } else {
+                Err(format!("Header '{}' value '{}' does not match '{}'", name, value, pattern))
+            }
+        }
+        ResponseAssertion::JsonPathEquals { path, value } => {
+            let values = query_json_path(&response.body, path)?;
+            let actual = values.first().ok_or_else(|| format!("JSONPath '{}' matched nothing", path))?;
+            if json_values_equal(actual, value) {
+                Ok(format!("{} equals {}", path, value))
+            } else {
+                Err(format!("Expected {} to equal {}, got {}", path, value, actual))
+            }
+        }
+        ResponseAssertion::JsonPathExists { path } => {
+            let values = query_json_path(&response.body, path)?;
+            if values.is_empty() {
+                Err(format!("JSONPath '{}' matched nothing", path))
+            } else {
+                Ok(format!("JSONPath '{}' matched {} value(s)", path, values.len()))
+            }
+        }
+        ResponseAssertion::BodyMatches { pattern } => {
+            let regex = regex::Regex::new(pattern).map_err(|e| format!("Invalid regex '{}': {}", pattern, e))?;
+            if regex.is_match(&response.body) {
+                Ok(format!("Body matches '{}'", pattern))
+            } else {
+                Err(format!("Body does not match '{}'", pattern))
+            }
+        }
+        ResponseAssertion::ResponseTimeBelow { ms } => {
+            let total = response
+                .timing
+                .as_ref()
+                .map(|timing| timing.total_ms)
+                .ok_or_else(|| "Response time is not available".to_string())?;
+            if total < *ms {
+                Ok(format!("Response time {:.1} ms is below {} ms", total, ms))
+            } else {
+                Err(format!("Response time {:.1} ms is not below {} ms", total, ms))
+            }
+        }
+        ResponseAssertion::JsonSchema { schema } => {
+            let validator = jsonschema::validator_for(schema).map_err(|e| format!("Invalid JSON Schema: {}", e))?;
+            let body: serde_json::Value =
+                serde_json::from_str(&response.body).map_err(|e| format!("Response body is not JSON: {}", e))?;
+            let errors: Vec<String> = validator
+                .iter_errors(&body)
+                .map(|error| {
+                    let location = error.instance_path.to_string();
+                    if location.is_empty() {
+                        error.to_string()
+                    } else {
+                        format!("{}: {}", location, error)
+                    }
+                })
+                .collect();
+            if errors.is_empty() {
+                Ok("Body matches the JSON Schema".to_string())
+            } else {
+                Err(errors.join("; "))
+            }
+        }
+    }
+}
+
+pub(crate) fn evaluate_assertions(assertions: &[ResponseAssertion], response: &HttpResponse) -> Vec<AssertionResult> {
+    assertions
+        .iter()
+        .map(|assertion| {
+            let (passed, message) = match check_assertion(assertion, response) {
+                Ok(message) => (true, message),
+                Err(message) => (false, message),
+            };
+            AssertionResult { assertion: assertion.clone(), passed, message }
+        })
+        .collect()
+}
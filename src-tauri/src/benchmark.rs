@@ -0,0 +1,284 @@
+//! HTTP benchmark
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use serde::{Deserialize, Serialize};
+use crate::http::{build_http_request, DEFAULT_HTTP_SESSION, describe_request_error, dispatch_http_request, HTTP_STREAM_COUNTER, HttpRequest, HttpSessions, HttpStreams, SendError};
+use crate::secrets::SecretStore;
+
+// Longest wait between two rate-limited requests, about one request a day
+const BENCHMARK_MAX_INTERVAL: std::time::Duration = std::time::Duration::from_secs(24 * 60 * 60);
+
+// Replay one request a number of times and/or for a duration. Either limit ends the run.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BenchmarkConfig {
+    request: HttpRequest,
+    iterations: Option<u64>,
+    duration_ms: Option<u64>,
+    concurrency: Option<usize>, // parallel workers, 1 by default, at most 1000
+    rate: Option<f64>,          // requests per second across all workers; unlimited when omitted
+}
+
+// Payload of the "http-benchmark-progress" event, sent about four times a second
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BenchmarkProgress {
+    request_id: String,
+    completed: u64,
+    errors: u64,
+    elapsed_ms: f64,
+    requests_per_sec: f64,
+}
+
+// Latencies in milliseconds of the requests that got a response
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct LatencyStats {
+    min: f64,
+    mean: f64,
+    p50: f64,
+    p90: f64,
+    p99: f64,
+    max: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BenchmarkReport {
+    request_id: String,
+    completed: u64, // responses plus errors
+    responses: u64,
+    errors: u64,
+    error_kinds: HashMap<String, u64>, // timeout, connect, redirect, body, decode, request or auth
+    status_codes: std::collections::BTreeMap<u16, u64>,
+    latency: LatencyStats,
+    bytes_received: u64,
+    elapsed_ms: f64,
+    requests_per_sec: f64,
+    cancelled: bool,
+}
+
+#[derive(Default)]
+struct BenchmarkStats {
+    latencies_ms: Vec<f64>,
+    errors: u64,
+    error_kinds: HashMap<String, u64>,
+    status_codes: std::collections::BTreeMap<u16, u64>,
+    bytes_received: u64,
+}
+
+impl BenchmarkStats {
+    fn completed(&self) -> u64 {
+        self.latencies_ms.len() as u64 + self.errors
+    }
+}
+
+// Nearest-rank percentile of sorted values
+fn percentile(sorted: &[f64], percent: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = (percent / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+fn latency_stats(latencies_ms: &[f64]) -> LatencyStats {
+    if latencies_ms.is_empty() {
+        return LatencyStats::default();
+    }
+    let mut sorted = latencies_ms.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    LatencyStats {
+        min: sorted[0],
+        mean: sorted.iter().sum::<f64>() / sorted.len() as f64,
+        p50: percentile(&sorted, 50.0),
+        p90: percentile(&sorted, 90.0),
+        p99: percentile(&sorted, 99.0),
+        max: sorted[sorted.len() - 1],
+    }
+}
+
+// Send requests until the iteration budget is used up; the caller aborts it on timeout or cancel
+async fn run_benchmark_worker(
+    client: reqwest::Client,
+    request: HttpRequest,
+    iterations: Option<u64>,
+    issued: std::sync::Arc<std::sync::atomic::AtomicU64>,
+    schedule: Option<(std::time::Duration, std::sync::Arc<Mutex<tokio::time::Instant>>)>,
+    stats: std::sync::Arc<Mutex<BenchmarkStats>>,
+) {
+    let auth = request.auth.clone();
+    loop {
+        let index = issued.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        if iterations.is_some_and(|iterations| index >= iterations) {
+            return;
+        }
+        // Rate limiting: every request takes the next free slot of the shared schedule
+        if let Some((interval, next_slot)) = &schedule {
+            let slot = match next_slot.lock() {
+                Ok(mut next) => {
+                    let slot = (*next).max(tokio::time::Instant::now());
+                    *next = slot + *interval;
+                    slot
+                }
+                Err(_) => return,
+            };
+            tokio::time::sleep_until(slot).await;
+        }
+
+        let started = std::time::Instant::now();
+        let result = match build_http_request(&client, request.clone()).and_then(|builder| {
+            builder.build().map_err(|e| describe_request_error(&e))
+        }) {
+            Ok(built) => match dispatch_http_request(&client, built, auth.as_ref()).await {
+                Ok(response) => {
+                    let status = response.status().as_u16();
+                    response
+                        .bytes()
+                        .await
+                        .map(|body| (status, body.len() as u64))
+                        .map_err(|e| SendError::Request(e).kind())
+                }
+                Err(e) => Err(e.kind()),
+            },
+            Err(_) => Err("request"),
+        };
+        let elapsed_ms = started.elapsed().as_secs_f64() * 1000.0;
+
+        let Ok(mut stats) = stats.lock() else { return };
+        match result {
+            Ok((status, size)) => {
+                stats.latencies_ms.push(elapsed_ms);
+                *stats.status_codes.entry(status).or_default() += 1;
+                stats.bytes_received += size;
+            }
+            Err(kind) => {
+                stats.errors += 1;
+                *stats.error_kinds.entry(kind.to_string()).or_default() += 1;
+            }
+        }
+    }
+}
+
+// Load-test one request. Progress is emitted as "http-benchmark-progress";
+// cancel_http_request with the same request id stops the run early.
+#[tauri::command]
+pub async fn run_http_benchmark(
+    app: tauri::AppHandle,
+    sessions: tauri::State<'_, HttpSessions>,
+    streams: tauri::State<'_, HttpStreams>,
+    secrets: tauri::State<'_, SecretStore>,
+    config: BenchmarkConfig,
+    request_id: Option<String>,
+) -> Result<BenchmarkReport, String> {
+    use tauri::Emitter;
+
+    if config.iterations.is_none() && config.duration_ms.is_none() {
+        return Err("Set iterations or duration_ms for the benchmark".to_string());
+    }
+    if config.rate.is_some_and(|rate| !rate.is_finite() || rate <= 0.0) {
+        return Err("Rate must be a positive number of requests per second".to_string());
+    }
+    let interval = match config.rate {
+        Some(rate) => Some(
+            std::time::Duration::try_from_secs_f64(1.0 / rate)
+                .ok()
+                .filter(|interval| *interval <= BENCHMARK_MAX_INTERVAL)
+                .ok_or_else(|| format!("Rate {} is too low; use at least one request a day", rate))?,
+        ),
+        None => None,
+    };
+    let mut request = secrets.resolve(config.request)?;
+    let mut options = request.options.clone().unwrap_or_default();
+    // Redirects are followed by the client; hops are not reported per request
+    options.capture_redirects = None;
+    request.options = Some(options.clone());
+    let session = request.session.as_deref().unwrap_or(DEFAULT_HTTP_SESSION);
+    let client = sessions.client(session, &options)?;
+    // Fail fast on a request that can never be sent
+    build_http_request(&client, request.clone())?
+        .build()
+        .map_err(|e| describe_request_error(&e))?;
+
+    let request_id = request_id
+        .filter(|id| !id.is_empty())
+        .unwrap_or_else(|| format!("http-{}", HTTP_STREAM_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed)));
+    let cancel = std::sync::Arc::new(tokio::sync::Notify::new());
+    {
+        let mut active = streams.0.lock().map_err(|_| "HTTP stream state is unavailable".to_string())?;
+        if active.contains_key(&request_id) {
+            return Err(format!("Request id already in use: {}", request_id));
+        }
+        active.insert(request_id.clone(), cancel.clone());
+    }
+
+    let started = tokio::time::Instant::now();
+    let deadline = config.duration_ms.map(|ms| started + std::time::Duration::from_millis(ms));
+    let issued = std::sync::Arc::new(std::sync::atomic::AtomicU64::new(0));
+    let stats = std::sync::Arc::new(Mutex::new(BenchmarkStats::default()));
+    let schedule = interval.map(|interval| (interval, std::sync::Arc::new(Mutex::new(started))));
+
+    let mut workers = tokio::task::JoinSet::new();
+    for _ in 0..config.concurrency.unwrap_or(1).clamp(1, 1000) {
+        workers.spawn(run_benchmark_worker(
+            client.clone(),
+            request.clone(),
+            config.iterations,
+            issued.clone(),
+            schedule.clone(),
+            stats.clone(),
+        ));
+    }
+
+    let progress = |stats: &Mutex<BenchmarkStats>| {
+        let elapsed = started.elapsed().as_secs_f64();
+        let (completed, errors) = stats.lock().map(|stats| (stats.completed(), stats.errors)).unwrap_or_default();
+        BenchmarkProgress {
+            request_id: request_id.clone(),
+            completed,
+            errors,
+            elapsed_ms: elapsed * 1000.0,
+            requests_per_sec: if elapsed > 0.0 { completed as f64 / elapsed } else { 0.0 },
+        }
+    };
+
+    let mut ticker = tokio::time::interval(std::time::Duration::from_millis(250));
+    let mut cancelled = false;
+    loop {
+        tokio::select! {
+            _ = cancel.notified() => {
+                cancelled = true;
+                break;
+            }
+            _ = tokio::time::sleep_until(deadline.unwrap_or(started)), if deadline.is_some() => break,
+            finished = workers.join_next() => {
+                if finished.is_none() {
+                    break;
+                }
+            }
+            _ = ticker.tick() => {
+                let _ = app.emit("http-benchmark-progress", progress(&stats));
+            }
+        }
+    }
+    // Requests still in flight at the deadline or on cancel are not counted
+    workers.shutdown().await;
+    if let Ok(mut active) = streams.0.lock() {
+        active.remove(&request_id);
+    }
+    let _ = app.emit("http-benchmark-progress", progress(&stats));
+
+    let elapsed = started.elapsed().as_secs_f64();
+    let stats = std::mem::take(&mut *stats.lock().map_err(|_| "Benchmark state is unavailable".to_string())?);
+    let completed = stats.completed();
+    Ok(BenchmarkReport {
+        request_id,
+        completed,
+        responses: stats.latencies_ms.len() as u64,
+        errors: stats.errors,
+        error_kinds: stats.error_kinds,
+        status_codes: stats.status_codes,
+        latency: latency_stats(&stats.latencies_ms),
+        bytes_received: stats.bytes_received,
+        elapsed_ms: elapsed * 1000.0,
+        requests_per_sec: if elapsed > 0.0 { completed as f64 / elapsed } else { 0.0 },
+        cancelled,
+    })
+}
@@ -0,0 +1,533 @@
+//! Capture proxy
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use base64::{Engine as _, engine::general_purpose};
+use serde::{Deserialize, Serialize};
+use crate::http::{describe_request_error, header_map_to_hash_map, HttpRequest, parse_content_type, RequestBody, unix_now_ms};
+use crate::mock_server::check_listen_host;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CaptureProxyConfig {
+    host: Option<String>,          // 127.0.0.1 when omitted
+    port: Option<u16>,             // a free port when omitted or 0
+    allow_remote: Option<bool>,    // required to listen on a host other than loopback
+    max_body_bytes: Option<usize>, // bodies stream through and are captured up to this size, 1 MiB by default
+    max_exchanges: Option<usize>,  // oldest exchanges are dropped past this, 500 by default
+    connect_timeout_ms: Option<u64>, // connecting to the origin, 10 s by default
+    read_timeout_ms: Option<u64>,    // longest wait for origin data on an open exchange, 60 s by default
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CaptureProxyInfo {
+    id: String,
+    url: String, // proxy URL to configure in the app being debugged
+    port: u16,
+}
+
+// One request/response pair seen by the proxy, also the payload of the "capture-proxy-exchange" event.
+// HTTPS goes through a CONNECT tunnel, which is captured without headers or bodies.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CapturedExchange {
+    proxy_id: String,
+    id: u64,
+    timestamp: u64, // unix milliseconds
+    method: String,
+    url: String,
+    request_headers: HashMap<String, String>,
+    request_body: Option<String>, // text when the captured body is UTF-8
+    request_body_base64: String,
+    request_body_size: usize,
+    status: u16, // 0 when the request failed
+    response_headers: HashMap<String, String>,
+    response_body: Option<String>,
+    response_body_base64: String,
+    response_body_size: usize,
+    request_body_truncated: bool, // larger than max_body_bytes
+    response_body_truncated: bool,
+    ttfb_ms: Option<f64>,
+    duration_ms: f64,
+    tunnel: bool,
+    bytes_sent: u64, // tunnel traffic from client to server
+    bytes_received: u64,
+    error: Option<String>,
+}
+
+struct CaptureProxyState {
+    id: String,
+    app: tauri::AppHandle,
+    client: reqwest::Client,
+    connect_timeout: std::time::Duration,
+    max_body_bytes: usize,
+    max_exchanges: usize,
+    exchanges: Mutex<std::collections::VecDeque<CapturedExchange>>,
+    next_id: std::sync::atomic::AtomicU64,
+}
+
+struct CaptureProxy {
+    info: CaptureProxyInfo,
+    state: std::sync::Arc<CaptureProxyState>,
+    task: tokio::task::JoinHandle<()>,
+}
+
+// Running capture proxies keyed by id
+#[derive(Default)]
+pub struct CaptureProxies(Mutex<HashMap<String, CaptureProxy>>);
+
+static CAPTURE_PROXY_COUNTER: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(1);
+const CAPTURE_MAX_BODY_LIMIT: usize = 16 * 1024 * 1024; // per captured body, kept for up to max_exchanges exchanges
+const CAPTURE_CONNECT_TIMEOUT_MS: u64 = 10_000;
+const CAPTURE_READ_TIMEOUT_MS: u64 = 60_000;
+
+// Connection-level headers that must not be forwarded by a proxy
+pub(crate) fn is_hop_by_hop_header(name: &str) -> bool {
+    matches!(
+        name.to_ascii_lowercase().as_str(),
+        "connection" | "proxy-connection" | "keep-alive" | "proxy-authorization" | "proxy-authenticate" | "te"
+            | "trailer" | "transfer-encoding" | "upgrade"
+    )
+}
+
+type ProxyBody = http_body_util::combinators::UnsyncBoxBody<hyper::body::Bytes, Box<dyn std::error::Error + Send + Sync>>;
+
+fn proxy_response(status: u16, message: String) -> hyper::Response<ProxyBody> {
+    use http_body_util::BodyExt;
+    let body = http_body_util::Full::new(hyper::body::Bytes::from(message)).map_err(|never| match never {});
+    let mut response = hyper::Response::new(body.boxed_unsync());
+    *response.status_mut() = hyper::StatusCode::from_u16(status).unwrap_or(hyper::StatusCode::BAD_GATEWAY);
+    response
+}
+
+// First max_body_bytes of a body as it streams through the proxy
+struct BodyCapture {
+    bytes: Vec<u8>,
+    size: usize,
+    limit: usize,
+    error: Option<String>, // the body could not be read
+}
+
+impl BodyCapture {
+    fn new(limit: usize) -> Self {
+        BodyCapture { bytes: Vec::new(), size: 0, limit, error: None }
+    }
+
+    fn push(&mut self, chunk: &[u8]) {
+        let room = self.limit.saturating_sub(self.bytes.len());
+        self.bytes.extend_from_slice(&chunk[..chunk.len().min(room)]);
+        self.size += chunk.len();
+    }
+
+    // Captured part: text when UTF-8, base64, full size, and whether it was cut
+    fn finish(&self) -> (Option<String>, String, usize, bool) {
+        (
+            std::str::from_utf8(&self.bytes).ok().map(str::to_string),
+            general_purpose::STANDARD.encode(&self.bytes),
+            self.size,
+            self.bytes.len() < self.size,
+        )
+    }
+}
+
+impl CaptureProxyState {
+    fn publish(&self, exchange: CapturedExchange) {
+        use tauri::Emitter;
+        let _ = self.app.emit("capture-proxy-exchange", exchange.clone());
+        if let Ok(mut exchanges) = self.exchanges.lock() {
+            exchanges.push_back(exchange);
+            while exchanges.len() > self.max_exchanges {
+                exchanges.pop_front();
+            }
+        }
+    }
+}
+
+fn fill_request_capture(exchange: &mut CapturedExchange, capture: &Mutex<BodyCapture>) {
+    if let Ok(capture) = capture.lock() {
+        let (text, base64, size, truncated) = capture.finish();
+        exchange.request_body = text;
+        exchange.request_body_base64 = base64;
+        exchange.request_body_size = size;
+        exchange.request_body_truncated = truncated;
+    }
+}
+
+// Relay a CONNECT tunnel once hyper hands over the client connection
+async fn run_capture_tunnel(
+    state: std::sync::Arc<CaptureProxyState>,
+    request: hyper::Request<hyper::body::Incoming>,
+    mut upstream: tokio::net::TcpStream,
+    mut exchange: CapturedExchange,
+    started: std::time::Instant,
+) {
+    let result = async {
+        let upgraded = hyper::upgrade::on(request)
+            .await
+            .map_err(|e| format!("Failed to open the tunnel: {}", e))?;
+        let mut client = hyper_util::rt::TokioIo::new(upgraded);
+        tokio::io::copy_bidirectional(&mut client, &mut upstream)
+            .await
+            .map_err(|e| format!("Tunnel closed with an error: {}", e))
+    }
+    .await;
+    match result {
+        Ok((sent, received)) => {
+            exchange.bytes_sent = sent;
+            exchange.bytes_received = received;
+        }
+        Err(e) => exchange.error = Some(e),
+    }
+    exchange.duration_ms = started.elapsed().as_secs_f64() * 1000.0;
+    state.publish(exchange);
+}
+
+// Stream the upstream response to the client, capturing its start, and publish the
+// exchange once the body has been relayed
+fn relay_capture_response(
+    state: std::sync::Arc<CaptureProxyState>,
+    mut response: reqwest::Response,
+    request_capture: std::sync::Arc<Mutex<BodyCapture>>,
+    mut exchange: CapturedExchange,
+    started: std::time::Instant,
+) -> hyper::Response<ProxyBody> {
+    use futures::SinkExt;
+    use http_body_util::BodyExt;
+
+    let status = response.status();
+    let mut headers = response.headers().clone();
+    exchange.status = status.as_u16();
+    exchange.response_headers = header_map_to_hash_map(&headers);
+    exchange.ttfb_ms = Some(started.elapsed().as_secs_f64() * 1000.0);
+    let hop_by_hop: Vec<_> = headers.keys().filter(|name| is_hop_by_hop_header(name.as_str())).cloned().collect();
+    for name in hop_by_hop {
+        headers.remove(name);
+    }
+
+    type Chunk = Result<hyper::body::Frame<hyper::body::Bytes>, Box<dyn std::error::Error + Send + Sync>>;
+    let (mut sender, receiver) = futures::channel::mpsc::channel::<Chunk>(16);
+    tokio::spawn(async move {
+        let mut capture = BodyCapture::new(state.max_body_bytes);
+        loop {
+            match response.chunk().await {
+                Ok(Some(chunk)) => {
+                    capture.push(&chunk);
+                    if sender.send(Ok(hyper::body::Frame::data(chunk))).await.is_err() {
+                        exchange.error = Some("The client closed the connection".to_string());
+                        break;
+                    }
+                }
+                Ok(None) => break,
+                Err(e) => {
+                    let message = format!("Error reading response body: {}", describe_request_error(&e));
+                    let _ = sender.send(Err(message.clone().into())).await;
+                    exchange.error = Some(message);
+                    break;
+                }
+            }
+        }
+        let (text, base64, size, truncated) = capture.finish();
+        exchange.response_body = text;
+        exchange.response_body_base64 = base64;
+        exchange.response_body_size = size;
+        exchange.response_body_truncated = truncated;
+        fill_request_capture(&mut exchange, &request_capture);
+        exchange.duration_ms = started.elapsed().as_secs_f64() * 1000.0;
+        state.publish(exchange);
+    });
+
+    let mut relayed = hyper::Response::new(http_body_util::StreamBody::new(receiver).boxed_unsync());
+    *relayed.status_mut() = status;
+    *relayed.headers_mut() = headers;
+    relayed
+}
+
+async fn handle_capture_request(
+    state: std::sync::Arc<CaptureProxyState>,
+    request: hyper::Request<hyper::body::Incoming>,
+) -> Result<hyper::Response<ProxyBody>, std::convert::Infallible> {
+    use futures::StreamExt;
+
+    let started = std::time::Instant::now();
+    let mut exchange = CapturedExchange {
+        proxy_id: state.id.clone(),
+        id: state.next_id.fetch_add(1, std::sync::atomic::Ordering::Relaxed),
+        timestamp: unix_now_ms(),
+        method: request.method().to_string(),
+        url: request.uri().to_string(),
+        request_headers: header_map_to_hash_map(request.headers()),
+        request_body: None,
+        request_body_base64: String::new(),
+        request_body_size: 0,
+        status: 0,
+        response_headers: HashMap::new(),
+        response_body: None,
+        response_body_base64: String::new(),
+        response_body_size: 0,
+        request_body_truncated: false,
+        response_body_truncated: false,
+        ttfb_ms: None,
+        duration_ms: 0.0,
+        tunnel: false,
+        bytes_sent: 0,
+        bytes_received: 0,
+        error: None,
+    };
+
+    if request.method() == hyper::Method::CONNECT {
+        // TLS 流量无法解密，只记录隧道信息；先连上游，再答复客户端
+        exchange.tunnel = true;
+        exchange.url = request.uri().authority().map(|authority| authority.to_string()).unwrap_or_default();
+        let connecting = tokio::net::TcpStream::connect(exchange.url.as_str());
+        let connected = match tokio::time::timeout(state.connect_timeout, connecting).await {
+            Ok(connected) => connected.map_err(|e| e.to_string()),
+            Err(_) => Err(format!("timed out after {} ms", state.connect_timeout.as_millis())),
+        };
+        return Ok(match connected {
+            Ok(upstream) => {
+                exchange.status = 200;
+                tokio::spawn(run_capture_tunnel(state.clone(), request, upstream, exchange, started));
+                proxy_response(200, String::new())
+            }
+            Err(e) => {
+                let message = format!("Failed to connect to {}: {}", exchange.url, e);
+                exchange.error = Some(message.clone());
+                exchange.duration_ms = started.elapsed().as_secs_f64() * 1000.0;
+                state.publish(exchange);
+                proxy_response(502, message)
+            }
+        });
+    }
+    if request.uri().scheme().is_none() {
+        return Ok(proxy_response(
+            400,
+            "This is an HTTP proxy: send absolute URLs through it instead of calling it directly".to_string(),
+        ));
+    }
+
+    // The request body streams to the upstream server while its start is captured
+    let (parts, incoming) = request.into_parts();
+    let request_capture = std::sync::Arc::new(Mutex::new(BodyCapture::new(state.max_body_bytes)));
+    let has_body = hyper::body::Body::size_hint(&incoming).exact() != Some(0);
+    let mut forwarded = state.client.request(parts.method.clone(), parts.uri.to_string());
+    if has_body {
+        let capture = request_capture.clone();
+        let stream = http_body_util::BodyDataStream::new(incoming).map(move |chunk| {
+            if let Ok(mut capture) = capture.lock() {
+                match &chunk {
+                    Ok(data) => capture.push(data),
+                    Err(e) => capture.error = Some(format!("Failed to read the request body: {}", e)),
+                }
+            }
+            chunk
+        });
+        forwarded = forwarded.body(reqwest::Body::wrap_stream(stream));
+    }
+    for (name, value) in &parts.headers {
+        if !is_hop_by_hop_header(name.as_str()) && name != hyper::header::HOST {
+            forwarded = forwarded.header(name, value);
+        }
+    }
+
+    match forwarded.send().await {
+        Ok(response) => Ok(relay_capture_response(state, response, request_capture, exchange, started)),
+        Err(e) => {
+            fill_request_capture(&mut exchange, &request_capture);
+            // A body the client failed to send is its own fault, anything else is the upstream's
+            let client_error = request_capture.lock().ok().and_then(|capture| capture.error.clone());
+            let (status, message) = match client_error {
+                Some(message) => (400, message),
+                None => (502, format!("Proxy request failed: {}", describe_request_error(&e))),
+            };
+            exchange.error = Some(message.clone());
+            exchange.duration_ms = started.elapsed().as_secs_f64() * 1000.0;
+            state.publish(exchange);
+            Ok(proxy_response(status, message))
+        }
+    }
+}
+
+async fn run_capture_proxy(listener: tokio::net::TcpListener, state: std::sync::Arc<CaptureProxyState>) {
+    let mut connections = tokio::task::JoinSet::new();
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => {
+                let Ok((stream, _)) = accepted else { continue };
+                let state = state.clone();
+                connections.spawn(async move {
+                    let service = hyper::service::service_fn(move |request| handle_capture_request(state.clone(), request));
+                    let _ = hyper::server::conn::http1::Builder::new()
+                        .serve_connection(hyper_util::rt::TokioIo::new(stream), service)
+                        .with_upgrades()
+                        .await;
+                });
+            }
+            Some(_) = connections.join_next(), if !connections.is_empty() => {}
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn start_capture_proxy(
+    app: tauri::AppHandle,
+    proxies: tauri::State<'_, CaptureProxies>,
+    config: CaptureProxyConfig,
+) -> Result<CaptureProxyInfo, String> {
+    let host = config.host.filter(|host| !host.is_empty()).unwrap_or_else(|| "127.0.0.1".to_string());
+    check_listen_host(&host, config.allow_remote)?;
+    let max_body_bytes = config.max_body_bytes.unwrap_or(1024 * 1024);
+    if max_body_bytes > CAPTURE_MAX_BODY_LIMIT {
+        return Err(format!("max_body_bytes must be at most {} bytes", CAPTURE_MAX_BODY_LIMIT));
+    }
+    let listener = tokio::net::TcpListener::bind((host.as_str(), config.port.unwrap_or(0)))
+        .await
+        .map_err(|e| format!("Failed to listen on {}:{}: {}", host, config.port.unwrap_or(0), e))?;
+    let port = listener
+        .local_addr()
+        .map_err(|e| format!("Failed to read the listening address: {}", e))?
+        .port();
+
+    let id = format!("proxy-{}", CAPTURE_PROXY_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed));
+    let info = CaptureProxyInfo {
+        id: id.clone(),
+        url: format!("http://{}:{}", if host.contains(':') { format!("[{}]", host) } else { host.clone() }, port),
+        port,
+    };
+    // Exchanges are relayed as they are: no redirects followed, no system proxy (it may be this one).
+    // The read timeout bounds each wait, so a long download that keeps flowing is not cut off.
+    let connect_timeout =
+        std::time::Duration::from_millis(config.connect_timeout_ms.filter(|ms| *ms > 0).unwrap_or(CAPTURE_CONNECT_TIMEOUT_MS));
+    let read_timeout =
+        std::time::Duration::from_millis(config.read_timeout_ms.filter(|ms| *ms > 0).unwrap_or(CAPTURE_READ_TIMEOUT_MS));
+    let client = reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .no_proxy()
+        .connect_timeout(connect_timeout)
+        .read_timeout(read_timeout)
+        .build()
+        .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
+    let state = std::sync::Arc::new(CaptureProxyState {
+        id: id.clone(),
+        app,
+        client,
+        connect_timeout,
+        max_body_bytes,
+        max_exchanges: config.max_exchanges.unwrap_or(500).max(1),
+        exchanges: Mutex::new(std::collections::VecDeque::new()),
+        next_id: std::sync::atomic::AtomicU64::new(1),
+    });
+    let task = tokio::spawn(run_capture_proxy(listener, state.clone()));
+
+    proxies
+        .0
+        .lock()
+        .map_err(|_| "Capture proxy state is unavailable".to_string())?
+        .insert(id, CaptureProxy { info: info.clone(), state, task });
+    Ok(info)
+}
+
+#[tauri::command]
+pub fn stop_capture_proxy(proxies: tauri::State<'_, CaptureProxies>, id: String) -> Result<bool, String> {
+    let proxy = proxies.0.lock().map_err(|_| "Capture proxy state is unavailable".to_string())?.remove(&id);
+    match proxy {
+        Some(proxy) => {
+            proxy.task.abort();
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+#[tauri::command]
+pub fn list_capture_proxies(proxies: tauri::State<'_, CaptureProxies>) -> Result<Vec<CaptureProxyInfo>, String> {
+    let proxies = proxies.0.lock().map_err(|_| "Capture proxy state is unavailable".to_string())?;
+    let mut list: Vec<CaptureProxyInfo> = proxies.values().map(|proxy| proxy.info.clone()).collect();
+    list.sort_by_key(|proxy| proxy.port);
+    Ok(list)
+}
+
+fn capture_proxy_state(
+    proxies: &CaptureProxies,
+    id: &str,
+) -> Result<std::sync::Arc<CaptureProxyState>, String> {
+    let proxies = proxies.0.lock().map_err(|_| "Capture proxy state is unavailable".to_string())?;
+    proxies
+        .get(id)
+        .map(|proxy| proxy.state.clone())
+        .ok_or_else(|| format!("Capture proxy not found: {}", id))
+}
+
+// Exchanges kept by a proxy, oldest first
+#[tauri::command]
+pub fn list_captured_exchanges(proxies: tauri::State<'_, CaptureProxies>, id: String) -> Result<Vec<CapturedExchange>, String> {
+    let state = capture_proxy_state(&proxies, &id)?;
+    let exchanges = state.exchanges.lock().map_err(|_| "Capture proxy state is unavailable".to_string())?;
+    Ok(exchanges.iter().cloned().collect())
+}
+
+#[tauri::command]
+pub fn clear_captured_exchanges(proxies: tauri::State<'_, CaptureProxies>, id: String) -> Result<(), String> {
+    let state = capture_proxy_state(&proxies, &id)?;
+    state.exchanges.lock().map_err(|_| "Capture proxy state is unavailable".to_string())?.clear();
+    Ok(())
+}
+
+// Request of a captured exchange, without the headers that belong to the proxy connection
+fn exchange_http_request(exchange: CapturedExchange) -> Result<HttpRequest, String> {
+    if exchange.tunnel {
+        return Err("HTTPS tunnels are captured without their requests".to_string());
+    }
+    if exchange.request_body_truncated {
+        return Err("The request body was truncated during capture".to_string());
+    }
+
+    let content_type = exchange
+        .request_headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("content-type"))
+        .map(|(_, value)| value.clone());
+    let body = match (exchange.request_body_size, exchange.request_body) {
+        (0, _) => None,
+        (_, Some(text)) if content_type.as_deref().is_some_and(|ct| parse_content_type(ct).0 == "application/json") => {
+            Some(RequestBody::Json { content: text })
+        }
+        (_, Some(text)) => Some(RequestBody::Text { content: text, content_type }),
+        (_, None) => Some(RequestBody::Binary {
+            path: None,
+            base64: Some(exchange.request_body_base64),
+            content_type,
+        }),
+    };
+    let headers = exchange
+        .request_headers
+        .into_iter()
+        .filter(|(name, _)| !is_hop_by_hop_header(name) && !matches!(name.as_str(), "host" | "content-length"))
+        .collect();
+
+    Ok(HttpRequest {
+        url: exchange.url,
+        method: exchange.method,
+        headers,
+        body,
+        options: None,
+        session: None,
+        auth: None,
+    })
+}
+
+// Turn a captured exchange into a request that can be edited and sent again
+#[tauri::command]
+pub fn captured_exchange_to_request(
+    proxies: tauri::State<'_, CaptureProxies>,
+    id: String,
+    exchange_id: u64,
+) -> Result<HttpRequest, String> {
+    let state = capture_proxy_state(&proxies, &id)?;
+    let exchange = state
+        .exchanges
+        .lock()
+        .map_err(|_| "Capture proxy state is unavailable".to_string())?
+        .iter()
+        .find(|exchange| exchange.id == exchange_id)
+        .cloned()
+        .ok_or_else(|| format!("Captured exchange not found: {}", exchange_id))?;
+    exchange_http_request(exchange)
+}
@@ -0,0 +1,317 @@
+//! Collection runner
+
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use crate::assertions::{evaluate_assertions, ResponseAssertion};
+use crate::http::{DEFAULT_HTTP_SESSION, execute_http_request, HttpRequest, HttpResponse, HttpSessions};
+use crate::secrets::SecretStore;
+
+// Value captured from a response into a variable for the following requests
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum VariableExtraction {
+    JsonPath {
+        variable: String,
+        path: String,
+    },
+    Header {
+        variable: String,
+        name: String,
+    },
+    Regex {
+        variable: String,
+        pattern: String,
+        group: Option<usize>, // first capture group when omitted, whole match without groups
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CollectionRequest {
+    pub(crate) name: Option<String>,
+    pub(crate) request: HttpRequest,
+    pub(crate) extract: Option<Vec<VariableExtraction>>,
+    pub(crate) assertions: Option<Vec<ResponseAssertion>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CollectionRunResult {
+    index: usize,
+    name: Option<String>,
+    request: HttpRequest, // as sent, after substitution
+    response: Option<HttpResponse>, // includes the assertion results
+    extracted: HashMap<String, String>,
+    unresolved: Vec<String>, // {{variables}} without a value, left as written
+    errors: Vec<String>,
+    passed: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CollectionRunReport {
+    results: Vec<CollectionRunResult>,
+    variables: HashMap<String, String>, // final values, including extracted ones
+    passed: usize,
+    failed: usize,
+    skipped: usize, // not run after a failure with stop_on_error
+    total_ms: f64,
+}
+
+// Replace {{name}} placeholders. Unknown names are kept as written and reported.
+pub(crate) fn substitute_variables(text: &str, variables: &HashMap<String, String>, unresolved: &mut Vec<String>) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let Some(length) = rest[start + 2..].find("}}") else { break };
+        let end = start + length + 4;
+        let name = rest[start + 2..start + 2 + length].trim();
+        result.push_str(&rest[..start]);
+        match variables.get(name) {
+            Some(value) => result.push_str(value),
+            None => {
+                if !unresolved.iter().any(|unknown| unknown == name) {
+                    unresolved.push(name.to_string());
+                }
+                result.push_str(&rest[start..end]);
+            }
+        }
+        rest = &rest[end..];
+    }
+    result.push_str(rest);
+    result
+}
+
+fn substitute_json(
+    value: serde_json::Value,
+    variables: &HashMap<String, String>,
+    unresolved: &mut Vec<String>,
+) -> serde_json::Value {
+    match value {
+        serde_json::Value::String(text) => serde_json::Value::String(substitute_variables(&text, variables, unresolved)),
+        serde_json::Value::Array(items) => serde_json::Value::Array(
+            items
+                .into_iter()
+                .map(|item| substitute_json(item, variables, unresolved))
+                .collect(),
+        ),
+        // Keys are structure, not text to substitute. Enum tags need no exception: they are
+        // fixed names without {{...}}, so only user values under a "type" key can change.
+        serde_json::Value::Object(fields) => serde_json::Value::Object(
+            fields
+                .into_iter()
+                .map(|(key, value)| (key, substitute_json(value, variables, unresolved)))
+                .collect(),
+        ),
+        other => other,
+    }
+}
+
+// Substitute variables in every string value of a serializable value: a request (URL,
+// headers, body, auth and options), its assertions, or a socket payload
+pub(crate) fn substitute_strings<T: Serialize + serde::de::DeserializeOwned>(
+    request: T,
+    variables: &HashMap<String, String>,
+    unresolved: &mut Vec<String>,
+) -> Result<T, String> {
+    let value = serde_json::to_value(request).map_err(|e| format!("Failed to apply variables: {}", e))?;
+    serde_json::from_value(substitute_json(value, variables, unresolved))
+        .map_err(|e| format!("Failed to apply variables: {}", e))
+}
+
+// Values selected by a JSONPath expression in a JSON response body
+pub(crate) fn query_json_path(body: &str, path: &str) -> Result<Vec<serde_json::Value>, String> {
+    let path = serde_json_path::JsonPath::parse(path).map_err(|e| format!("Invalid JSONPath '{}': {}", path, e))?;
+    let body: serde_json::Value =
+        serde_json::from_str(body).map_err(|e| format!("Response body is not JSON: {}", e))?;
+    Ok(path.query(&body).all().into_iter().cloned().collect())
+}
+
+// Strings as-is, other JSON values serialized
+pub(crate) fn json_value_text(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+pub(crate) fn response_header<'a>(response: &'a HttpResponse, name: &str) -> Option<&'a String> {
+    response
+        .headers
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value)
+}
+
+fn extract_variable(extraction: &VariableExtraction, response: &HttpResponse) -> Result<(String, String), String> {
+    match extraction {
+        VariableExtraction::JsonPath { variable, path } => {
+            let values = query_json_path(&response.body, path)?;
+            let value = values
+                .first()
+                .ok_or_else(|| format!("JSONPath '{}' matched nothing", path))?;
+            Ok((variable.clone(), json_value_text(value)))
+        }
+        VariableExtraction::Header { variable, name } => {
+            let value = response_header(response, name).ok_or_else(|| format!("Header '{}' not found", name))?;
+            Ok((variable.clone(), value.clone()))
+        }
+        VariableExtraction::Regex { variable, pattern, group } => {
+            let regex = regex::Regex::new(pattern).map_err(|e| format!("Invalid regex '{}': {}", pattern, e))?;
+            let captures = regex
+                .captures(&response.body)
+                .ok_or_else(|| format!("Regex '{}' did not match", pattern))?;
+            let group = group.unwrap_or(if captures.len() > 1 { 1 } else { 0 });
+            let value = captures
+                .get(group)
+                .ok_or_else(|| format!("Regex '{}' has no match for group {}", pattern, group))?;
+            Ok((variable.clone(), value.as_str().to_string()))
+        }
+    }
+}
+
+async fn run_collection(
+    sessions: &HttpSessions,
+    secrets: &SecretStore,
+    requests: Vec<CollectionRequest>,
+    mut variables: HashMap<String, String>,
+    stop_on_error: bool,
+) -> CollectionRunReport {
+    let started = std::time::Instant::now();
+    let mut results = Vec::new();
+    let mut skipped = 0;
+
+    for (index, item) in requests.into_iter().enumerate() {
+        if stop_on_error && results.iter().any(|result: &CollectionRunResult| !result.passed) {
+            skipped += 1;
+            continue;
+        }
+
+        let mut unresolved = Vec::new();
+        let mut errors = Vec::new();
+        let mut extracted = HashMap::new();
+        let request = match substitute_strings(item.request.clone(), &variables, &mut unresolved) {
+            Ok(request) => request,
+            Err(e) => {
+                errors.push(e);
+                item.request
+            }
+        };
+        // Secrets are resolved separately and only for sending; the reported request keeps the references
+        unresolved.retain(|name| !name.starts_with("secret:"));
+
+        let mut response = None;
+        if errors.is_empty() {
+            let options = request.options.clone().unwrap_or_default();
+            let session = request.session.as_deref().unwrap_or(DEFAULT_HTTP_SESSION);
+            let mask = secrets.mask(&request);
+            let result = match (sessions.client(session, &options), secrets.resolve(request.clone())) {
+                (Ok(client), Ok(resolved)) => execute_http_request(&client, resolved).await,
+                (Err(e), _) | (_, Err(e)) => Err(e),
+            };
+            match result {
+                Ok(mut sent) => {
+                    mask.apply_response(&mut sent);
+                    if let Some(error) = &sent.error {
+                        errors.push(error.clone());
+                    }
+                    response = Some(sent);
+                }
+                Err(e) => errors.push(mask.apply(&e)),
+            }
+        }
+
+        if let (Some(sent), Some(assertions)) = (response.as_mut(), item.assertions) {
+            match substitute_strings(assertions, &variables, &mut unresolved) {
+                Ok(assertions) => sent.assertions = evaluate_assertions(&assertions, sent),
+                Err(e) => errors.push(e),
+            }
+        }
+
+        if let Some(sent) = response.as_ref().filter(|sent| sent.error.is_none()) {
+            for extraction in item.extract.iter().flatten() {
+                match extract_variable(extraction, sent) {
+                    Ok((name, value)) => {
+                        variables.insert(name.clone(), value.clone());
+                        extracted.insert(name, value);
+                    }
+                    Err(e) => errors.push(e),
+                }
+            }
+        }
+
+        let passed = errors.is_empty()
+            && response
+                .as_ref()
+                .is_none_or(|sent| sent.assertions.iter().all(|result| result.passed));
+        results.push(CollectionRunResult {
+            index,
+            name: item.name,
+            request,
+            response,
+            extracted,
+            unresolved,
+            errors,
+            passed,
+        });
+    }
+
+    let passed = results.iter().filter(|result| result.passed).count();
+    CollectionRunReport {
+        failed: results.len() - passed,
+        passed,
+        skipped,
+        results,
+        variables,
+        total_ms: started.elapsed().as_secs_f64() * 1000.0,
+    }
+}
+
+// Run requests in order with {{variables}} taken from the environment and from values
+// extracted out of earlier responses
+#[tauri::command]
+pub async fn run_http_collection(
+    sessions: tauri::State<'_, HttpSessions>,
+    secrets: tauri::State<'_, SecretStore>,
+    requests: Vec<CollectionRequest>,
+    variables: Option<HashMap<String, String>>,
+    stop_on_error: Option<bool>,
+) -> Result<CollectionRunReport, String> {
+    let variables = variables.unwrap_or_default();
+    Ok(run_collection(&sessions, &secrets, requests, variables, stop_on_error.unwrap_or(false)).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn substitutes_values_under_type_keys() {
+        let request: HttpRequest = serde_json::from_value(serde_json::json!({
+            "url": "https://{{host}}/graphql",
+            "method": "POST",
+            "headers": { "type": "{{kind}}" },
+            "body": {
+                "type": "graphql",
+                "query": "query($filter: Filter) { items(filter: $filter) { id } }",
+                "variables": { "filter": { "type": "{{kind}}", "tags": [{ "type": "{{kind}}" }] } }
+            },
+            "auth": { "type": "bearer", "token": "{{token}}" }
+        }))
+        .unwrap();
+        let variables = HashMap::from([
+            ("host".to_string(), "example.com".to_string()),
+            ("kind".to_string(), "book".to_string()),
+            ("token".to_string(), "abc".to_string()),
+        ]);
+        let mut unresolved = Vec::new();
+        let request = substitute_strings(request, &variables, &mut unresolved).unwrap();
+
+        assert!(unresolved.is_empty());
+        let value = serde_json::to_value(&request).unwrap();
+        assert_eq!(value["url"], "https://example.com/graphql");
+        assert_eq!(value["headers"]["type"], "book");
+        assert_eq!(value["body"]["type"], "graphql");
+        assert_eq!(value["body"]["variables"]["filter"]["type"], "book");
+        assert_eq!(value["body"]["variables"]["filter"]["tags"][0]["type"], "book");
+        assert_eq!(value["auth"]["type"], "bearer");
+        assert_eq!(value["auth"]["token"], "abc");
+    }
+}
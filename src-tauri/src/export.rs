@@ -0,0 +1,663 @@
+//! Request code export
+
+use base64::{Engine as _, engine::general_purpose};
+use crate::http::{apply_request_auth, graphql_payload, graphql_query_params, HttpProtocol, HttpRequest, HttpRequestOptions, MultipartEntry, parse_http_method, RequestAuth, RequestBody};
+
+// Body of an exported request, with GraphQL and base64 content already resolved
+enum ExportBody {
+    Raw(String),
+    Bytes(Vec<u8>),
+    File(String),
+    Form(Vec<(String, String)>),
+    Multipart(Vec<MultipartEntry>),
+}
+
+// HttpRequest resolved for code export: final URL, headers in a stable order including
+// the default Content-Type and the credentials of the auth scheme
+struct ExportRequest {
+    method: reqwest::Method,
+    url: String,
+    headers: Vec<(String, String)>,
+    body: Option<ExportBody>,
+    digest: Option<(String, String)>, // left to the target tool, needs a server challenge
+    signed: bool,                     // headers carry a signature that expires
+    options: HttpRequestOptions,
+}
+
+// File part of a multipart body: (path, filename, mime type). Inline base64 files are
+// referenced by their filename.
+fn export_file_part(entry: &MultipartEntry) -> (String, String, String) {
+    let path = entry.path.clone().filter(|path| !path.is_empty());
+    let filename = entry.filename.clone().filter(|name| !name.is_empty()).unwrap_or_else(|| {
+        path.as_deref()
+            .and_then(|path| std::path::Path::new(path).file_name())
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| entry.key.clone())
+    });
+    let content_type = entry
+        .content_type
+        .clone()
+        .filter(|ct| !ct.is_empty())
+        .unwrap_or_else(|| mime_guess::from_path(&filename).first_or_octet_stream().to_string());
+    (path.unwrap_or_else(|| filename.clone()), filename, content_type)
+}
+
+fn export_part_headers(entry: &MultipartEntry) -> Vec<(String, String)> {
+    let mut headers: Vec<(String, String)> = entry.headers.clone().unwrap_or_default().into_iter().collect();
+    headers.sort();
+    headers
+}
+
+// application/x-www-form-urlencoded encoding of one key or value
+fn form_urlencode(value: &str) -> String {
+    const FORM_ESCAPE: &percent_encoding::AsciiSet = &percent_encoding::NON_ALPHANUMERIC
+        .remove(b'*')
+        .remove(b'-')
+        .remove(b'.')
+        .remove(b'_')
+        .remove(b' ');
+    percent_encoding::utf8_percent_encode(value, FORM_ESCAPE).to_string().replace(' ', "+")
+}
+
+fn prepare_export_request(request: HttpRequest) -> Result<ExportRequest, String> {
+    let method = parse_http_method(&request.method)?;
+    let mut url = reqwest::Url::parse(request.url.trim()).map_err(|e| format!("Invalid URL '{}': {}", request.url, e))?;
+    let mut headers: Vec<(String, String)> = request
+        .headers
+        .into_iter()
+        .filter(|(key, _)| !key.trim().is_empty())
+        .collect();
+    headers.sort();
+    let has_content_type = headers.iter().any(|(key, _)| key.eq_ignore_ascii_case("content-type"));
+
+    let (body, content_type) = match request.body {
+        None => (None, None),
+        Some(RequestBody::Text { content, content_type }) => {
+            let content_type = content_type.filter(|ct| !ct.is_empty()).unwrap_or_else(|| "text/plain; charset=utf-8".to_string());
+            (Some(ExportBody::Raw(content)), Some(content_type))
+        }
+        Some(RequestBody::Json { content }) => (Some(ExportBody::Raw(content)), Some("application/json".to_string())),
+        Some(RequestBody::Urlencoded { fields }) => {
+            let pairs = fields.into_iter().map(|field| (field.key, field.value)).collect();
+            (Some(ExportBody::Form(pairs)), Some("application/x-www-form-urlencoded".to_string()))
+        }
+        Some(RequestBody::Binary { path, base64, content_type }) => {
+            let path = path.filter(|path| !path.is_empty());
+            let content_type = content_type.filter(|ct| !ct.is_empty()).unwrap_or_else(|| {
+                path.as_deref()
+                    .map(|path| mime_guess::from_path(path).first_or_octet_stream().to_string())
+                    .unwrap_or_else(|| "application/octet-stream".to_string())
+            });
+            let body = match (path, base64) {
+                (Some(path), _) => Some(ExportBody::File(path)),
+                (None, Some(base64)) => Some(ExportBody::Bytes(
+                    general_purpose::STANDARD
+                        .decode(base64.trim())
+                        .map_err(|e| format!("Invalid base64 body: {}", e))?,
+                )),
+                (None, None) => None,
+            };
+            (body, Some(content_type))
+        }
+        Some(RequestBody::Multipart { entries }) => {
+            // The target tool generates the boundary
+            headers.retain(|(key, _)| !key.eq_ignore_ascii_case("content-type"));
+            (Some(ExportBody::Multipart(entries)), None)
+        }
+        Some(RequestBody::Graphql { query, variables, operation_name }) => {
+            if method == reqwest::Method::GET {
+                url.query_pairs_mut()
+                    .extend_pairs(graphql_query_params(query, variables, operation_name));
+                (None, None)
+            } else {
+                let payload = graphql_payload(query, variables, operation_name);
+                (Some(ExportBody::Raw(payload.to_string())), Some("application/json".to_string()))
+            }
+        }
+    };
+    if let Some(content_type) = content_type.filter(|_| !has_content_type) {
+        headers.push(("Content-Type".to_string(), content_type));
+    }
+
+    // Credentials other than Digest are computed now and exported as plain headers
+    let mut digest = None;
+    let mut signed = false;
+    match request.auth {
+        None => {}
+        Some(RequestAuth::Digest { username, password }) => digest = Some((username, password)),
+        Some(auth) => {
+            signed = matches!(auth, RequestAuth::AwsSigv4 { .. } | RequestAuth::Oauth1 { .. });
+            let mut snapshot = reqwest::Request::new(method.clone(), url.clone());
+            for (key, value) in &headers {
+                let name = reqwest::header::HeaderName::from_bytes(key.trim().as_bytes())
+                    .map_err(|e| format!("Invalid header name '{}': {}", key, e))?;
+                let value = reqwest::header::HeaderValue::from_str(value)
+                    .map_err(|e| format!("Invalid value for header '{}': {}", key, e))?;
+                snapshot.headers_mut().append(name, value);
+            }
+            *snapshot.body_mut() = match &body {
+                None => None,
+                Some(ExportBody::Raw(text)) => Some(text.clone().into()),
+                Some(ExportBody::Bytes(bytes)) => Some(bytes.clone().into()),
+                Some(ExportBody::Form(pairs)) => Some(
+                    pairs
+                        .iter()
+                        .map(|(key, value)| format!("{}={}", form_urlencode(key), form_urlencode(value)))
+                        .collect::<Vec<_>>()
+                        .join("&")
+                        .into(),
+                ),
+                // Streamed bodies are signed without a payload hash
+                Some(ExportBody::File(_)) | Some(ExportBody::Multipart(_)) => Some(reqwest::Body::wrap_stream(
+                    futures::stream::empty::<Result<Vec<u8>, std::io::Error>>(),
+                )),
+            };
+            apply_request_auth(&mut snapshot, &auth)?;
+
+            url = snapshot.url().clone();
+            for (name, value) in snapshot.headers() {
+                let value = String::from_utf8_lossy(value.as_bytes()).into_owned();
+                if !headers.iter().any(|(key, existing)| key.eq_ignore_ascii_case(name.as_str()) && *existing == value) {
+                    headers.retain(|(key, _)| !key.eq_ignore_ascii_case(name.as_str()));
+                    headers.push((name.to_string(), value));
+                }
+            }
+        }
+    }
+
+    Ok(ExportRequest {
+        method,
+        url: url.to_string(),
+        headers,
+        body,
+        digest,
+        signed,
+        options: request.options.unwrap_or_default(),
+    })
+}
+
+// POSIX shell word: left bare when it only contains safe characters, else single-quoted
+fn shell_quote(value: &str) -> String {
+    let is_safe = |c: char| c.is_ascii_alphanumeric() || "-_./:=@%+,".contains(c);
+    if !value.is_empty() && value.chars().all(is_safe) {
+        value.to_string()
+    } else {
+        format!("'{}'", value.replace('\'', "'\\''"))
+    }
+}
+
+// Double-quoted string literal, valid in JSON, JavaScript and Python
+fn json_literal(value: &str) -> String {
+    serde_json::Value::from(value).to_string()
+}
+
+// Value inside a curl -F argument, quoted when it contains separators
+fn curl_form_value(value: &str) -> String {
+    if value.contains([';', ',', '"']) || value.contains(char::is_whitespace) {
+        format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn export_timeout_secs(options: &HttpRequestOptions) -> Option<String> {
+    options.timeout_ms.map(|ms| format!("{}", ms as f64 / 1000.0))
+}
+
+fn export_as_curl(request: &ExportRequest) -> String {
+    let mut prefix = String::new();
+    let mut suffix = String::new();
+    let mut args = vec!["curl".to_string()];
+    match request.method {
+        reqwest::Method::HEAD => args[0].push_str(" --head"),
+        reqwest::Method::GET if request.body.is_none() => {}
+        ref method => args[0].push_str(&format!(" -X {}", method)),
+    }
+    args[0].push_str(&format!(" {}", shell_quote(&request.url)));
+
+    let options = &request.options;
+    match options.max_redirects {
+        Some(0) => {}
+        Some(max) => args.push(format!("-L --max-redirs {}", max)),
+        None => args.push("-L".to_string()),
+    }
+    if options.insecure.unwrap_or(false) {
+        args.push("-k".to_string());
+    }
+    if options.decompress == Some(true) {
+        args.push("--compressed".to_string());
+    }
+    match options.protocol.unwrap_or_default() {
+        HttpProtocol::Auto => {}
+        HttpProtocol::Http1 => args.push("--http1.1".to_string()),
+        HttpProtocol::Http2 => args.push("--http2-prior-knowledge".to_string()),
+    }
+    if let Some(proxy) = options.proxy_url.as_deref().filter(|proxy| !proxy.is_empty()) {
+        args.push(format!("-x {}", shell_quote(proxy)));
+    }
+    if let Some(timeout) = export_timeout_secs(options) {
+        args.push(format!("--max-time {}", timeout));
+    }
+    if let Some((username, password)) = &request.digest {
+        args.push(format!("--digest -u {}", shell_quote(&format!("{}:{}", username, password))));
+    }
+    for (key, value) in &request.headers {
+        args.push(format!("-H {}", shell_quote(&format!("{}: {}", key, value))));
+    }
+
+    match &request.body {
+        None => {}
+        Some(ExportBody::Raw(text)) => args.push(format!("--data-raw {}", shell_quote(text))),
+        Some(ExportBody::Bytes(bytes)) => {
+            prefix = format!("printf %s {} | base64 -d | ", general_purpose::STANDARD.encode(bytes));
+            args.push("--data-binary @-".to_string());
+        }
+        Some(ExportBody::File(path)) => args.push(format!("--data-binary {}", shell_quote(&format!("@{}", path)))),
+        Some(ExportBody::Form(pairs)) => {
+            for (key, value) in pairs {
+                args.push(format!("--data-urlencode {}", shell_quote(&format!("{}={}", form_urlencode(key), value))));
+            }
+        }
+        Some(ExportBody::Multipart(entries)) => {
+            for entry in entries {
+                // curl takes the field name verbatim up to the first '=', without unquoting,
+                // so ';' and '"' pass through as written but a name with '=' cannot be given
+                if entry.key.contains('=') {
+                    suffix.push_str(&format!("\n# Form field {} left out: curl -F names cannot contain '='", shell_quote(&entry.key)));
+                    continue;
+                }
+                if entry.r#type == "file" {
+                    let (path, filename, content_type) = export_file_part(entry);
+                    let mut field = format!(
+                        "{}=@{};filename={};type={}",
+                        entry.key,
+                        curl_form_value(&path),
+                        curl_form_value(&filename),
+                        content_type
+                    );
+                    for (key, value) in export_part_headers(entry) {
+                        field.push_str(&format!(";headers={}", curl_form_value(&format!("{}: {}", key, value))));
+                    }
+                    args.push(format!("-F {}", shell_quote(&field)));
+                } else {
+                    args.push(format!("--form-string {}", shell_quote(&format!("{}={}", entry.key, entry.value))));
+                }
+            }
+        }
+    }
+    if request.signed {
+        suffix.push_str("\n# The signature is computed at export time and expires");
+    }
+    format!("{}{}{}", prefix, args.join(" \\\n  "), suffix)
+}
+
+// HTTPie request item: separators in the key are escaped
+fn httpie_item(key: &str, separator: &str, value: &str) -> String {
+    let mut escaped = String::new();
+    for c in key.chars() {
+        if matches!(c, '\\' | ':' | '=' | '@' | ';') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    shell_quote(&format!("{}{}{}", escaped, separator, value))
+}
+
+fn export_as_httpie(request: &ExportRequest) -> String {
+    let mut prefix = String::new();
+    let mut suffix = String::new();
+    let mut flags = Vec::new();
+    let mut items = Vec::new();
+
+    let options = &request.options;
+    match options.max_redirects {
+        Some(0) => {}
+        Some(max) => flags.push(format!("--follow --max-redirects={}", max)),
+        None => flags.push("--follow".to_string()),
+    }
+    if options.insecure.unwrap_or(false) {
+        flags.push("--verify=no".to_string());
+    }
+    if let Some(proxy) = options.proxy_url.as_deref().filter(|proxy| !proxy.is_empty()) {
+        flags.push(format!("--proxy={}", shell_quote(&format!("http:{}", proxy))));
+        flags.push(format!("--proxy={}", shell_quote(&format!("https:{}", proxy))));
+    }
+    if let Some(timeout) = export_timeout_secs(options) {
+        flags.push(format!("--timeout={}", timeout));
+    }
+    if let Some((username, password)) = &request.digest {
+        flags.push(format!("-A digest -a {}", shell_quote(&format!("{}:{}", username, password))));
+    }
+    for (key, value) in &request.headers {
+        items.push(httpie_item(key, ":", value));
+    }
+
+    match &request.body {
+        None => {}
+        Some(ExportBody::Raw(text)) => flags.push(format!("--raw {}", shell_quote(text))),
+        Some(ExportBody::Bytes(bytes)) => {
+            prefix = format!("printf %s {} | base64 -d | ", general_purpose::STANDARD.encode(bytes));
+        }
+        Some(ExportBody::File(path)) => suffix = format!(" < {}", shell_quote(path)),
+        Some(ExportBody::Form(pairs)) => {
+            flags.push("--form".to_string());
+            items.extend(pairs.iter().map(|(key, value)| httpie_item(key, "=", value)));
+        }
+        Some(ExportBody::Multipart(entries)) => {
+            flags.push("--multipart".to_string());
+            for entry in entries {
+                if entry.r#type == "file" {
+                    let (path, _, content_type) = export_file_part(entry);
+                    items.push(httpie_item(&entry.key, "@", &format!("{};type={}", path, content_type)));
+                } else {
+                    items.push(httpie_item(&entry.key, "=", &entry.value));
+                }
+            }
+        }
+    }
+
+    let mut args = vec![format!("http {}", flags.join(" ")).trim_end().to_string()];
+    args.push(format!("{} {}", request.method, shell_quote(&request.url)));
+    args.extend(items);
+    let mut command = format!("{}{}{}", prefix, args.join(" \\\n  "), suffix);
+    if request.signed {
+        command.push_str("\n# The signature is computed at export time and expires");
+    }
+    command
+}
+
+fn export_as_python(request: &ExportRequest) -> String {
+    let mut imports = vec!["import requests".to_string()];
+    let mut lines = Vec::new();
+    let mut args = vec![json_literal(request.method.as_str()), "url".to_string()];
+
+    if request.signed {
+        lines.push("# The signature is computed at export time and expires".to_string());
+    }
+    lines.push(format!("url = {}", json_literal(&request.url)));
+    if !request.headers.is_empty() {
+        lines.push("headers = {".to_string());
+        for (key, value) in &request.headers {
+            lines.push(format!("    {}: {},", json_literal(key), json_literal(value)));
+        }
+        lines.push("}".to_string());
+        args.push("headers=headers".to_string());
+    }
+
+    match &request.body {
+        None => {}
+        Some(ExportBody::Raw(text)) => lines.push(format!("data = {}", json_literal(text))),
+        Some(ExportBody::Bytes(bytes)) => lines.push(format!("data = b\"{}\"", bytes.escape_ascii())),
+        Some(ExportBody::File(path)) => lines.push(format!("data = open({}, \"rb\")", json_literal(path))),
+        Some(ExportBody::Form(pairs)) => {
+            lines.push("data = [".to_string());
+            for (key, value) in pairs {
+                lines.push(format!("    ({}, {}),", json_literal(key), json_literal(value)));
+            }
+            lines.push("]".to_string());
+        }
+        Some(ExportBody::Multipart(entries)) => {
+            lines.push("files = [".to_string());
+            for entry in entries {
+                if entry.r#type == "file" {
+                    let (path, filename, content_type) = export_file_part(entry);
+                    let headers = export_part_headers(entry);
+                    let headers = if headers.is_empty() {
+                        String::new()
+                    } else {
+                        let fields: Vec<String> = headers
+                            .iter()
+                            .map(|(key, value)| format!("{}: {}", json_literal(key), json_literal(value)))
+                            .collect();
+                        format!(", {{{}}}", fields.join(", "))
+                    };
+                    lines.push(format!(
+                        "    ({}, ({}, open({}, \"rb\"), {}{})),",
+                        json_literal(&entry.key),
+                        json_literal(&filename),
+                        json_literal(&path),
+                        json_literal(&content_type),
+                        headers
+                    ));
+                } else {
+                    lines.push(format!("    ({}, (None, {})),", json_literal(&entry.key), json_literal(&entry.value)));
+                }
+            }
+            lines.push("]".to_string());
+            args.push("files=files".to_string());
+        }
+    }
+    if matches!(request.body, Some(ExportBody::Raw(_) | ExportBody::Bytes(_) | ExportBody::File(_) | ExportBody::Form(_))) {
+        args.push("data=data".to_string());
+    }
+
+    if let Some((username, password)) = &request.digest {
+        imports.push("from requests.auth import HTTPDigestAuth".to_string());
+        args.push(format!("auth=HTTPDigestAuth({}, {})", json_literal(username), json_literal(password)));
+    }
+    let options = &request.options;
+    if options.insecure.unwrap_or(false) {
+        args.push("verify=False".to_string());
+    }
+    if let Some(proxy) = options.proxy_url.as_deref().filter(|proxy| !proxy.is_empty()) {
+        args.push(format!("proxies={{\"http\": {0}, \"https\": {0}}}", json_literal(proxy)));
+    }
+    if let Some(timeout) = export_timeout_secs(options) {
+        args.push(format!("timeout={}", timeout));
+    }
+    if options.max_redirects == Some(0) {
+        args.push("allow_redirects=False".to_string());
+    }
+
+    format!(
+        "{}\n\n{}\nresponse = requests.request(\n    {},\n)\n\nprint(response.status_code)\nprint(response.text)\n",
+        imports.join("\n"),
+        lines.join("\n"),
+        args.join(",\n    ")
+    )
+}
+
+fn export_as_fetch(request: &ExportRequest) -> String {
+    let mut lines = Vec::new();
+    let mut init = vec![format!("method: {}", json_literal(request.method.as_str()))];
+    let mut unsupported = Vec::new();
+    let reads_files = match &request.body {
+        Some(ExportBody::File(_)) => true,
+        Some(ExportBody::Multipart(entries)) => entries.iter().any(|entry| entry.r#type == "file"),
+        _ => false,
+    };
+    if reads_files {
+        lines.push("import { readFile } from \"node:fs/promises\";".to_string());
+        lines.push(String::new());
+    }
+    if request.signed {
+        lines.push("// The signature is computed at export time and expires".to_string());
+    }
+
+    if !request.headers.is_empty() {
+        let headers: Vec<String> = request
+            .headers
+            .iter()
+            .map(|(key, value)| format!("    {}: {},", json_literal(key), json_literal(value)))
+            .collect();
+        init.push(format!("headers: {{\n{}\n  }}", headers.join("\n")));
+    }
+
+    match &request.body {
+        None => {}
+        Some(ExportBody::Raw(text)) => init.push(format!("body: {}", json_literal(text))),
+        Some(ExportBody::Bytes(bytes)) => init.push(format!(
+            "body: Uint8Array.from(atob({}), (c) => c.charCodeAt(0))",
+            json_literal(&general_purpose::STANDARD.encode(bytes))
+        )),
+        Some(ExportBody::File(path)) => init.push(format!("body: await readFile({})", json_literal(path))),
+        Some(ExportBody::Form(pairs)) => {
+            let pairs: Vec<String> = pairs
+                .iter()
+                .map(|(key, value)| format!("    [{}, {}],", json_literal(key), json_literal(value)))
+                .collect();
+            init.push(format!("body: new URLSearchParams([\n{}\n  ])", pairs.join("\n")));
+        }
+        Some(ExportBody::Multipart(entries)) => {
+            lines.push("const form = new FormData();".to_string());
+            for entry in entries {
+                if entry.r#type == "file" {
+                    let (path, filename, content_type) = export_file_part(entry);
+                    if entry.headers.as_ref().is_some_and(|headers| !headers.is_empty()) {
+                        unsupported.push(format!("part headers of \"{}\"", entry.key));
+                    }
+                    lines.push(format!(
+                        "form.append({}, new Blob([await readFile({})], {{ type: {} }}), {});",
+                        json_literal(&entry.key),
+                        json_literal(&path),
+                        json_literal(&content_type),
+                        json_literal(&filename)
+                    ));
+                } else {
+                    lines.push(format!("form.append({}, {});", json_literal(&entry.key), json_literal(&entry.value)));
+                }
+            }
+            lines.push(String::new());
+            init.push("body: form".to_string());
+        }
+    }
+
+    let options = &request.options;
+    if options.max_redirects == Some(0) {
+        init.push("redirect: \"manual\"".to_string());
+    }
+    if let Some(timeout) = options.timeout_ms {
+        init.push(format!("signal: AbortSignal.timeout({})", timeout));
+    }
+    if request.digest.is_some() {
+        unsupported.push("Digest authentication".to_string());
+    }
+    if options.insecure.unwrap_or(false) {
+        unsupported.push("skipping certificate verification".to_string());
+    }
+    if options.proxy_url.as_deref().is_some_and(|proxy| !proxy.is_empty()) {
+        unsupported.push("proxy".to_string());
+    }
+    if options.protocol.is_some_and(|protocol| protocol != HttpProtocol::Auto) {
+        unsupported.push("choosing the HTTP version".to_string());
+    }
+    if !unsupported.is_empty() {
+        lines.push(format!("// Not available with fetch: {}", unsupported.join(", ")));
+    }
+
+    lines.push(format!(
+        "const response = await fetch({}, {{\n  {},\n}});",
+        json_literal(&request.url),
+        init.join(",\n  ")
+    ));
+    lines.push(String::new());
+    lines.push("console.log(response.status);".to_string());
+    lines.push("console.log(await response.text());".to_string());
+    lines.join("\n") + "\n"
+}
+
+fn export_as_reqwest(request: &ExportRequest) -> String {
+    let mut lines = Vec::new();
+    if request.signed {
+        lines.push("// The signature is computed at export time and expires".to_string());
+    }
+    if let Some((username, _)) = &request.digest {
+        lines.push(format!(
+            "// Digest authentication is not built into reqwest (user {:?})",
+            username
+        ));
+    }
+
+    let options = &request.options;
+    let mut client = Vec::new();
+    if options.insecure.unwrap_or(false) {
+        client.push(".danger_accept_invalid_certs(true)".to_string());
+    }
+    if let Some(proxy) = options.proxy_url.as_deref().filter(|proxy| !proxy.is_empty()) {
+        client.push(format!(".proxy(reqwest::Proxy::all({:?})?)", proxy));
+    }
+    if let Some(timeout) = options.timeout_ms {
+        client.push(format!(".timeout(std::time::Duration::from_millis({}))", timeout));
+    }
+    match options.protocol.unwrap_or_default() {
+        HttpProtocol::Auto => {}
+        HttpProtocol::Http1 => client.push(".http1_only()".to_string()),
+        HttpProtocol::Http2 => client.push(".http2_prior_knowledge()".to_string()),
+    }
+    match options.max_redirects {
+        Some(0) => client.push(".redirect(reqwest::redirect::Policy::none())".to_string()),
+        Some(max) => client.push(format!(".redirect(reqwest::redirect::Policy::limited({}))", max)),
+        None => {}
+    }
+    if client.is_empty() {
+        lines.push("let client = reqwest::Client::new();".to_string());
+    } else {
+        lines.push(format!(
+            "let client = reqwest::Client::builder()\n    {}\n    .build()?;",
+            client.join("\n    ")
+        ));
+    }
+
+    let mut builder = vec![format!(".request(reqwest::Method::{}, {:?})", request.method, request.url)];
+    for (key, value) in &request.headers {
+        builder.push(format!(".header({:?}, {:?})", key, value));
+    }
+    match &request.body {
+        None => {}
+        Some(ExportBody::Raw(text)) => builder.push(format!(".body({:?})", text)),
+        Some(ExportBody::Bytes(bytes)) => builder.push(format!(".body(b\"{}\".to_vec())", bytes.escape_ascii())),
+        Some(ExportBody::File(path)) => builder.push(format!(".body(std::fs::read({:?})?)", path)),
+        Some(ExportBody::Form(pairs)) => {
+            let pairs: Vec<String> = pairs.iter().map(|(key, value)| format!("({:?}, {:?})", key, value)).collect();
+            builder.push(format!(".form(&[{}])", pairs.join(", ")));
+        }
+        Some(ExportBody::Multipart(entries)) => {
+            let mut form = vec!["let form = reqwest::multipart::Form::new()".to_string()];
+            for entry in entries {
+                if entry.r#type == "file" {
+                    let (path, filename, content_type) = export_file_part(entry);
+                    let mut part = format!(
+                        "reqwest::multipart::Part::bytes(std::fs::read({:?})?)\n            .file_name({:?})\n            .mime_str({:?})?",
+                        path, filename, content_type
+                    );
+                    let headers = export_part_headers(entry);
+                    if !headers.is_empty() {
+                        let pairs: Vec<String> = headers.iter().map(|(key, value)| format!("({:?}, {:?})", key, value)).collect();
+                        part.push_str(&format!(
+                            "\n            .headers(reqwest::header::HeaderMap::try_from(&std::collections::HashMap::from([{}]))?)",
+                            pairs.join(", ")
+                        ));
+                    }
+                    form.push(format!("    .part(\n        {:?},\n        {},\n    )", entry.key, part));
+                } else {
+                    form.push(format!("    .text({:?}, {:?})", entry.key, entry.value));
+                }
+            }
+            lines.push(form.join("\n") + ";");
+            builder.push(".multipart(form)".to_string());
+        }
+    }
+    builder.push(".send()".to_string());
+    builder.push(".await?;".to_string());
+
+    lines.push(format!("let response = client\n    {}", builder.join("\n    ")));
+    lines.push(String::new());
+    lines.push("println!(\"{}\", response.status());".to_string());
+    lines.push("println!(\"{}\", response.text().await?);".to_string());
+    lines.join("\n") + "\n"
+}
+
+// Render a request as a curl or HTTPie command, or a Python requests, JavaScript fetch
+// or Rust reqwest snippet
+#[tauri::command]
+pub fn export_http_request(request: HttpRequest, format: String) -> Result<String, String> {
+    let request = prepare_export_request(request)?;
+    match format.as_str() {
+        "curl" => Ok(export_as_curl(&request)),
+        "httpie" => Ok(export_as_httpie(&request)),
+        "python" => Ok(export_as_python(&request)),
+        "fetch" => Ok(export_as_fetch(&request)),
+        "reqwest" => Ok(export_as_reqwest(&request)),
+        _ => Err(format!("Unsupported export format: {}", format)),
+    }
+}
@@ -0,0 +1,544 @@
+//! gRPC client
+
+use std::collections::HashMap;
+use std::fs;
+use base64::{Engine as _, engine::general_purpose};
+use serde::{Deserialize, Serialize};
+use crate::http::{build_http_client, build_http_tls_config, decode_content_encoding, describe_request_error, header_map_to_hash_map, HttpRequestOptions, HttpStreams, unix_now_ms, url_authority};
+use crate::secrets::SecretStore;
+
+// Where service definitions come from: .proto files or a compiled descriptor set
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct GrpcSchemaSource {
+    #[serde(default)]
+    proto_files: Vec<String>,
+    #[serde(default)]
+    include_paths: Vec<String>, // import roots; the folders of proto_files when empty
+    descriptor_set_path: Option<String>, // protoc --descriptor_set_out --include_imports
+    descriptor_set_base64: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GrpcService {
+    name: String, // fully qualified, e.g. helloworld.Greeter
+    methods: Vec<GrpcMethod>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GrpcMethod {
+    name: String,
+    path: String, // /package.Service/Method
+    input_type: String,
+    output_type: String,
+    client_streaming: bool,
+    server_streaming: bool,
+    request_template: serde_json::Value, // input message with every field at its default
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GrpcRequest {
+    url: String,     // http:// or https:// address of the server
+    service: String, // fully qualified service name
+    method: String,
+    #[serde(default)]
+    message: serde_json::Value, // request in the protobuf JSON mapping
+    #[serde(default)]
+    metadata: HashMap<String, String>, // -bin keys take base64 values
+    source: GrpcSchemaSource,
+    web: Option<bool>, // gRPC-Web through the HTTP client instead of native gRPC over HTTP/2
+    emit_defaults: Option<bool>, // include fields left at their default value in decoded messages
+    max_message_bytes: Option<usize>, // largest response message accepted, 4 MiB by default
+    options: Option<HttpRequestOptions>, // timeout_ms becomes the call deadline; proxy_url and protocol only apply to gRPC-Web
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct GrpcResponse {
+    status_code: Option<u32>, // None when no gRPC status was received
+    status_name: Option<String>,
+    status_message: Option<String>,
+    http_status: Option<u16>,
+    headers: HashMap<String, String>,
+    trailers: HashMap<String, String>,
+    messages: Vec<serde_json::Value>,
+    duration_ms: u64,
+    error: Option<String>,
+}
+
+// Payload of the "grpc-message" event, sent for each message of a server stream
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GrpcStreamMessage {
+    request_id: String,
+    index: usize,
+    message: serde_json::Value,
+    timestamp: u64,
+}
+
+const GRPC_STATUS_NAMES: [&str; 17] = [
+    "OK",
+    "CANCELLED",
+    "UNKNOWN",
+    "INVALID_ARGUMENT",
+    "DEADLINE_EXCEEDED",
+    "NOT_FOUND",
+    "ALREADY_EXISTS",
+    "PERMISSION_DENIED",
+    "RESOURCE_EXHAUSTED",
+    "FAILED_PRECONDITION",
+    "ABORTED",
+    "OUT_OF_RANGE",
+    "UNIMPLEMENTED",
+    "INTERNAL",
+    "UNAVAILABLE",
+    "DATA_LOSS",
+    "UNAUTHENTICATED",
+];
+
+fn load_grpc_schema(source: &GrpcSchemaSource) -> Result<prost_reflect::DescriptorPool, String> {
+    let descriptor_set = match (&source.descriptor_set_path, &source.descriptor_set_base64) {
+        (Some(path), _) if !path.is_empty() => {
+            Some(fs::read(path).map_err(|e| format!("Failed to read descriptor set {}: {}", path, e))?)
+        }
+        (_, Some(data)) if !data.is_empty() => Some(
+            general_purpose::STANDARD
+                .decode(data.trim())
+                .map_err(|e| format!("Invalid base64 descriptor set: {}", e))?,
+        ),
+        _ => None,
+    };
+    if let Some(bytes) = descriptor_set {
+        return prost_reflect::DescriptorPool::decode(bytes.as_slice())
+            .map_err(|e| format!("Failed to load descriptor set: {}", e));
+    }
+
+    if source.proto_files.is_empty() {
+        return Err("No .proto files or descriptor set given".to_string());
+    }
+    let mut includes: Vec<std::path::PathBuf> = source.include_paths.iter().map(std::path::PathBuf::from).collect();
+    if includes.is_empty() {
+        for file in &source.proto_files {
+            let folder = std::path::Path::new(file)
+                .parent()
+                .filter(|folder| !folder.as_os_str().is_empty())
+                .unwrap_or(std::path::Path::new("."))
+                .to_path_buf();
+            if !includes.contains(&folder) {
+                includes.push(folder);
+            }
+        }
+    }
+    let mut compiler = protox::Compiler::new(includes).map_err(|e| format!("Failed to compile .proto files: {}", e))?;
+    compiler
+        .include_imports(true)
+        .open_files(&source.proto_files)
+        .map_err(|e| format!("Failed to compile .proto files: {}", e))?;
+    Ok(compiler.descriptor_pool())
+}
+
+fn grpc_method_path(method: &prost_reflect::MethodDescriptor) -> String {
+    format!("/{}/{}", method.parent_service().full_name(), method.name())
+}
+
+fn grpc_message_json(message: &prost_reflect::DynamicMessage, emit_defaults: bool) -> Result<serde_json::Value, String> {
+    let options = prost_reflect::SerializeOptions::new().skip_default_fields(!emit_defaults);
+    message
+        .serialize_with_options(serde_json::value::Serializer, &options)
+        .map_err(|e| format!("Failed to convert {} to JSON: {}", prost_reflect::ReflectMessage::descriptor(message).full_name(), e))
+}
+
+// Load a schema and list its services with their methods
+#[tauri::command]
+pub fn load_grpc_services(source: GrpcSchemaSource) -> Result<Vec<GrpcService>, String> {
+    let pool = load_grpc_schema(&source)?;
+    pool.services()
+        .map(|service| {
+            let methods = service
+                .methods()
+                .map(|method| {
+                    let template = prost_reflect::DynamicMessage::new(method.input());
+                    Ok(GrpcMethod {
+                        name: method.name().to_string(),
+                        path: grpc_method_path(&method),
+                        input_type: method.input().full_name().to_string(),
+                        output_type: method.output().full_name().to_string(),
+                        client_streaming: method.is_client_streaming(),
+                        server_streaming: method.is_server_streaming(),
+                        request_template: grpc_message_json(&template, true)?,
+                    })
+                })
+                .collect::<Result<Vec<_>, String>>()?;
+            Ok(GrpcService {
+                name: service.full_name().to_string(),
+                methods,
+            })
+        })
+        .collect()
+}
+
+// Default limit on the size of a response message, the same as most gRPC libraries
+const GRPC_MAX_MESSAGE_SIZE: usize = 4 * 1024 * 1024;
+
+// Reassembles length-prefixed messages from the response body and decodes them
+struct GrpcReader<'a> {
+    app: &'a tauri::AppHandle,
+    request_id: Option<&'a str>, // set when messages are also emitted as events
+    output: prost_reflect::MessageDescriptor,
+    emit_defaults: bool,
+    max_message_size: usize, // applies to messages as sent and once decompressed
+    encoding: Option<String>, // grpc-encoding of compressed messages
+    buffer: Vec<u8>,
+    response: GrpcResponse,
+}
+
+impl GrpcReader<'_> {
+    fn read_headers(&mut self, status: u16, headers: &reqwest::header::HeaderMap) -> Result<(), String> {
+        self.response.http_status = Some(status);
+        self.response.headers = header_map_to_hash_map(headers);
+        self.encoding = headers
+            .get("grpc-encoding")
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        let content_type = headers
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default();
+        if !content_type.starts_with("application/grpc") && !headers.contains_key("grpc-status") {
+            return Err(format!(
+                "Not a gRPC response: HTTP {} with content type {}",
+                status,
+                if content_type.is_empty() { "(none)" } else { content_type }
+            ));
+        }
+        Ok(())
+    }
+
+    fn push(&mut self, data: &[u8]) -> Result<(), String> {
+        use tauri::Emitter;
+
+        self.buffer.extend_from_slice(data);
+        while self.buffer.len() >= 5 {
+            let length = u32::from_be_bytes([self.buffer[1], self.buffer[2], self.buffer[3], self.buffer[4]]) as usize;
+            // Checked before buffering the rest, so an oversized message is never held in memory
+            if length > self.max_message_size {
+                return Err(format!("Message of {} bytes is larger than the {} byte limit", length, self.max_message_size));
+            }
+            if self.buffer.len() < 5 + length {
+                break;
+            }
+            let flags = self.buffer[0];
+            let payload: Vec<u8> = self.buffer.drain(..5 + length).skip(5).collect();
+
+            // gRPC-Web sends the trailers as a final frame with the high bit set
+            if flags & 0x80 != 0 {
+                for line in String::from_utf8_lossy(&payload).lines() {
+                    if let Some((name, value)) = line.split_once(':') {
+                        self.response
+                            .trailers
+                            .insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+                    }
+                }
+                continue;
+            }
+            let payload = if flags & 0x01 != 0 {
+                let encoding = self.encoding.as_deref().unwrap_or("identity");
+                decode_content_encoding(encoding, &payload, self.max_message_size)?
+                    .ok_or_else(|| format!("Unsupported grpc-encoding: {}", encoding))?
+            } else {
+                payload
+            };
+            let message = prost_reflect::DynamicMessage::decode(self.output.clone(), payload.as_slice())
+                .map_err(|e| format!("Failed to decode {}: {}", self.output.full_name(), e))?;
+            let message = grpc_message_json(&message, self.emit_defaults)?;
+            if let Some(request_id) = self.request_id {
+                let _ = self.app.emit(
+                    "grpc-message",
+                    GrpcStreamMessage {
+                        request_id: request_id.to_string(),
+                        index: self.response.messages.len(),
+                        message: message.clone(),
+                        timestamp: unix_now_ms(),
+                    },
+                );
+            }
+            self.response.messages.push(message);
+        }
+        Ok(())
+    }
+
+    // Take the status from the trailers, or from the headers of a trailers-only response
+    fn finish(mut self, trailers: Option<&reqwest::header::HeaderMap>) -> GrpcResponse {
+        if let Some(trailers) = trailers {
+            self.response.trailers.extend(header_map_to_hash_map(trailers));
+        }
+        if !self.buffer.is_empty() && self.response.error.is_none() {
+            self.response.error = Some("Response ended in the middle of a message".to_string());
+        }
+        let response = &mut self.response;
+        let field = |name: &str| response.trailers.get(name).or_else(|| response.headers.get(name)).cloned();
+        let code = field("grpc-status").and_then(|status| status.trim().parse::<u32>().ok());
+        let message = field("grpc-message")
+            .map(|message| percent_encoding::percent_decode_str(&message).decode_utf8_lossy().into_owned());
+        if let Some(code) = code {
+            response.status_code = Some(code);
+            response.status_name = Some(GRPC_STATUS_NAMES.get(code as usize).unwrap_or(&"UNKNOWN").to_string());
+            response.status_message = message.filter(|message| !message.is_empty());
+        } else if response.error.is_none() {
+            response.error = Some(match response.http_status {
+                Some(status) if status != 200 => format!("Server answered HTTP {} without a gRPC status", status),
+                _ => "The response carried no grpc-status".to_string(),
+            });
+        }
+        self.response
+    }
+}
+
+// grpc-timeout takes at most 8 digits, so long deadlines move to a coarser unit
+fn grpc_timeout(ms: u64) -> String {
+    for (scale, unit) in [(1, 'm'), (1_000, 'S'), (60_000, 'M'), (3_600_000, 'H')] {
+        let value = ms.div_ceil(scale);
+        if value < 100_000_000 {
+            return format!("{}{}", value, unit);
+        }
+    }
+    "99999999H".to_string()
+}
+
+fn grpc_request_headers(request: &GrpcRequest, web: bool, timeout_ms: Option<u64>) -> Result<reqwest::header::HeaderMap, String> {
+    use reqwest::header::{HeaderName, HeaderValue};
+
+    let mut headers = reqwest::header::HeaderMap::new();
+    let content_type = if web { "application/grpc-web+proto" } else { "application/grpc" };
+    headers.insert(reqwest::header::CONTENT_TYPE, HeaderValue::from_static(content_type));
+    if web {
+        headers.insert(reqwest::header::ACCEPT, HeaderValue::from_static(content_type));
+        headers.insert("x-grpc-web", HeaderValue::from_static("1"));
+    } else {
+        headers.insert(reqwest::header::TE, HeaderValue::from_static("trailers"));
+    }
+    headers.insert("grpc-accept-encoding", HeaderValue::from_static("gzip, deflate"));
+    if let Some(ms) = timeout_ms {
+        headers.insert("grpc-timeout", HeaderValue::from_str(&grpc_timeout(ms)).map_err(|e| e.to_string())?);
+    }
+    for (name, value) in &request.metadata {
+        let name = name.trim().to_ascii_lowercase();
+        let header = HeaderName::from_bytes(name.as_bytes()).map_err(|e| format!("Invalid metadata key {}: {}", name, e))?;
+        let value = HeaderValue::from_str(value.trim()).map_err(|e| format!("Invalid metadata value for {}: {}", name, e))?;
+        headers.insert(header, value);
+    }
+    Ok(headers)
+}
+
+// Native gRPC: one HTTP/2 stream over a fresh connection
+async fn grpc_native_call(
+    url: &reqwest::Url,
+    path: &str,
+    headers: reqwest::header::HeaderMap,
+    body: Vec<u8>,
+    options: &HttpRequestOptions,
+    reader: &mut GrpcReader<'_>,
+) -> Result<Option<reqwest::header::HeaderMap>, String> {
+    let host = url.host_str().ok_or_else(|| format!("Invalid gRPC URL: {}", url))?.to_string();
+    let port = url.port_or_known_default().unwrap_or(80);
+    let tcp = tokio::net::TcpStream::connect((host.trim_start_matches('[').trim_end_matches(']'), port))
+        .await
+        .map_err(|e| format!("Failed to connect to {}:{}: {}", host, port, e))?;
+    let _ = tcp.set_nodelay(true);
+
+    let mut request = hyper::http::Request::builder()
+        .method("POST")
+        .uri(format!("{}://{}{}", url.scheme(), url_authority(url), path))
+        .body(())
+        .map_err(|e| format!("Invalid gRPC request: {}", e))?;
+    *request.headers_mut() = headers;
+
+    if url.scheme() == "https" {
+        let mut tls = build_http_tls_config(options)?;
+        tls.alpn_protocols = vec![b"h2".to_vec()];
+        let server_name = rustls::pki_types::ServerName::try_from(host.trim_start_matches('[').trim_end_matches(']').to_string())
+            .map_err(|e| format!("Invalid server name {}: {}", host, e))?;
+        let stream = tokio_rustls::TlsConnector::from(std::sync::Arc::new(tls))
+            .connect(server_name, tcp)
+            .await
+            .map_err(|e| format!("TLS handshake failed: {}", e))?;
+        if stream.get_ref().1.alpn_protocol() != Some(b"h2") {
+            return Err("The server did not negotiate HTTP/2 (h2) over TLS".to_string());
+        }
+        grpc_h2_exchange(stream, request, body, reader).await
+    } else {
+        grpc_h2_exchange(tcp, request, body, reader).await
+    }
+}
+
+async fn grpc_h2_exchange<T>(
+    io: T,
+    request: hyper::http::Request<()>,
+    body: Vec<u8>,
+    reader: &mut GrpcReader<'_>,
+) -> Result<Option<reqwest::header::HeaderMap>, String>
+where
+    T: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin + Send + 'static,
+{
+    let (client, connection) = h2::client::handshake(io)
+        .await
+        .map_err(|e| format!("HTTP/2 handshake failed: {}", e))?;
+    let connection = tokio::spawn(connection);
+
+    let result = async {
+        let mut client = client.ready().await.map_err(|e| format!("HTTP/2 connection failed: {}", e))?;
+        let (response, mut send) = client
+            .send_request(request, false)
+            .map_err(|e| format!("Failed to send gRPC request: {}", e))?;
+        send.send_data(body.into(), true)
+            .map_err(|e| format!("Failed to send gRPC request: {}", e))?;
+        let response = response.await.map_err(|e| format!("gRPC call failed: {}", e))?;
+        reader.read_headers(response.status().as_u16(), response.headers())?;
+
+        let mut body = response.into_body();
+        while let Some(chunk) = body.data().await {
+            let chunk = chunk.map_err(|e| format!("gRPC stream failed: {}", e))?;
+            let _ = body.flow_control().release_capacity(chunk.len());
+            reader.push(&chunk)?;
+        }
+        body.trailers().await.map_err(|e| format!("Failed to read gRPC trailers: {}", e))
+    }
+    .await;
+    connection.abort();
+    result
+}
+
+// gRPC-Web: an ordinary HTTP request whose body also carries the trailers
+async fn grpc_web_call(
+    url: reqwest::Url,
+    headers: reqwest::header::HeaderMap,
+    body: Vec<u8>,
+    options: &HttpRequestOptions,
+    reader: &mut GrpcReader<'_>,
+) -> Result<Option<reqwest::header::HeaderMap>, String> {
+    let client = build_http_client(options, None)?;
+    let mut response = client
+        .post(url)
+        .headers(headers)
+        .body(body)
+        .send()
+        .await
+        .map_err(|e| describe_request_error(&e))?;
+    reader.read_headers(response.status().as_u16(), response.headers())?;
+    while let Some(chunk) = response.chunk().await.map_err(|e| describe_request_error(&e))? {
+        reader.push(&chunk)?;
+    }
+    Ok(None)
+}
+
+// Call a unary or server-streaming method. With a request_id, each message of a server stream
+// is also emitted as "grpc-message" and the call can be aborted with cancel_http_request.
+#[tauri::command]
+pub async fn grpc_call(
+    app: tauri::AppHandle,
+    streams: tauri::State<'_, HttpStreams>,
+    secrets: tauri::State<'_, SecretStore>,
+    request: GrpcRequest,
+    request_id: Option<String>,
+) -> Result<GrpcResponse, String> {
+    use prost_reflect::prost::Message;
+
+    let mask = secrets.mask(&request);
+    let request = secrets.resolve(request)?;
+    let pool = load_grpc_schema(&request.source)?;
+    let service = pool
+        .get_service_by_name(&request.service)
+        .ok_or_else(|| format!("Service not found: {}", request.service))?;
+    let method = service
+        .methods()
+        .find(|method| method.name() == request.method)
+        .ok_or_else(|| format!("Method not found: {}/{}", request.service, request.method))?;
+    if method.is_client_streaming() {
+        return Err("Client-streaming and bidirectional methods are not supported".to_string());
+    }
+    let message = if request.message.is_null() {
+        prost_reflect::DynamicMessage::new(method.input())
+    } else {
+        prost_reflect::DynamicMessage::deserialize(method.input(), request.message.clone())
+            .map_err(|e| format!("Invalid {} message: {}", method.input().full_name(), e))?
+    };
+    let encoded = message.encode_to_vec();
+    let mut body = Vec::with_capacity(encoded.len() + 5);
+    body.push(0);
+    body.extend_from_slice(&(encoded.len() as u32).to_be_bytes());
+    body.extend_from_slice(&encoded);
+
+    let url = reqwest::Url::parse(&request.url).map_err(|e| format!("Invalid gRPC URL: {}", e))?;
+    if url.scheme() != "http" && url.scheme() != "https" {
+        return Err(format!("Unsupported gRPC URL scheme: {}", url.scheme()));
+    }
+    let path = grpc_method_path(&method);
+    let options = request.options.clone().unwrap_or_default();
+    let timeout_ms = options.timeout_ms.filter(|ms| *ms > 0);
+    let web = request.web.unwrap_or(false);
+    let headers = grpc_request_headers(&request, web, timeout_ms)?;
+
+    let request_id = request_id.filter(|id| !id.is_empty());
+    let cancel = std::sync::Arc::new(tokio::sync::Notify::new());
+    if let Some(id) = &request_id {
+        let mut active = streams.0.lock().map_err(|_| "HTTP stream state is unavailable".to_string())?;
+        if active.contains_key(id) {
+            return Err(format!("Request id already in use: {}", id));
+        }
+        active.insert(id.clone(), cancel.clone());
+    }
+
+    let mut reader = GrpcReader {
+        app: &app,
+        request_id: request_id.as_deref().filter(|_| method.is_server_streaming()),
+        output: method.output(),
+        emit_defaults: request.emit_defaults.unwrap_or(false),
+        max_message_size: request.max_message_bytes.unwrap_or(GRPC_MAX_MESSAGE_SIZE),
+        encoding: None,
+        buffer: Vec::new(),
+        response: GrpcResponse::default(),
+    };
+    let started = std::time::Instant::now();
+    let call = async {
+        if web {
+            // Appended to the base path, as gRPC-Web servers are often mounted below the root
+            let mut target = url.clone();
+            target.set_path(&format!("{}{}", url.path().trim_end_matches('/'), path));
+            grpc_web_call(target, headers, body, &options, &mut reader).await
+        } else {
+            grpc_native_call(&url, &path, headers, body, &options, &mut reader).await
+        }
+    };
+    let deadline = async {
+        match timeout_ms {
+            Some(ms) => tokio::time::sleep(std::time::Duration::from_millis(ms)).await,
+            None => std::future::pending().await,
+        }
+    };
+    // Local failures are reported with the status a gRPC client library would use
+    let outcome = tokio::select! {
+        result = call => result.map_err(|e| (2, e)),
+        _ = deadline => Err((4, format!("Deadline of {} ms exceeded", timeout_ms.unwrap_or_default()))),
+        _ = cancel.notified() => Err((1, "Cancelled by the client".to_string())),
+    };
+
+    if let Some(id) = &request_id {
+        if let Ok(mut active) = streams.0.lock() {
+            active.remove(id);
+        }
+    }
+    let mut response = match outcome {
+        Ok(trailers) => reader.finish(trailers.as_ref()),
+        Err((code, error)) => {
+            let mut response = std::mem::take(&mut reader.response);
+            if matches!(code, 1 | 4) {
+                response.status_code = Some(code);
+                response.status_name = Some(GRPC_STATUS_NAMES[code as usize].to_string());
+            }
+            response.error = Some(error);
+            response
+        }
+    };
+    mask.apply_headers(&mut response.headers);
+    mask.apply_headers(&mut response.trailers);
+    mask.apply_error(&mut response.error);
+    response.duration_ms = started.elapsed().as_millis() as u64;
+    Ok(response)
+}
@@ -0,0 +1,655 @@
+//! HTTP history
+
+use std::collections::HashMap;
+use std::fs;
+use std::sync::Mutex;
+use serde::{Deserialize, Serialize};
+use crate::assertions::json_values_equal;
+use crate::http::{DEFAULT_HTTP_SESSION, execute_http_request, HttpRequest, HttpResponse, HttpSessions, RequestAuth, unix_now_ms};
+use crate::secrets::SecretStore;
+
+const HTTP_HISTORY_FILE: &str = "http-history.jsonl";
+const HTTP_HISTORY_ID_FILE: &str = "http-history.last-id";
+const HTTP_HISTORY_LIMIT: usize = 1000; // oldest entries are dropped past this
+const HTTP_HISTORY_BODY_LIMIT: usize = 1024 * 1024; // bytes of response body kept per entry
+const HISTORY_REDACTED: &str = "[redacted]";
+const HISTORY_SENSITIVE_HEADERS: [&str; 7] = [
+    "authorization",
+    "proxy-authorization",
+    "cookie",
+    "set-cookie",
+    "x-api-key",
+    "x-auth-token",
+    "x-amz-security-token",
+];
+
+// Environment the request was sent with, as chosen in the UI
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HistoryEnvironment {
+    name: Option<String>,
+    #[serde(default)]
+    variables: HashMap<String, String>,
+}
+
+// Credentials, sensitive headers and environment values are redacted before an entry is
+// stored, and the response keeps a single body copy: the text, or base64 for binary bodies
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HistoryEntry {
+    id: u64,
+    timestamp: u64, // unix milliseconds
+    request: HttpRequest,
+    response: HttpResponse, // includes the timing of the request
+    environment: Option<HistoryEnvironment>,
+    #[serde(default)]
+    redacted: Vec<String>, // what was redacted from the request, e.g. "header Authorization"
+    #[serde(default)]
+    body_truncated: bool, // the body was cut at HTTP_HISTORY_BODY_LIMIT
+}
+
+// Entry without the request and response bodies, for listings
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HistorySummary {
+    id: u64,
+    timestamp: u64,
+    method: String,
+    url: String,
+    status: u16,
+    error: Option<String>,
+    total_ms: Option<f64>,
+    body_size: usize,
+    environment: Option<String>,
+}
+
+// All filters are optional; results are newest first
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct HistoryQuery {
+    url: Option<String>, // case-insensitive substring
+    method: Option<String>,
+    status: Option<u16>,
+    status_min: Option<u16>,
+    status_max: Option<u16>,
+    from: Option<u64>, // unix milliseconds, inclusive
+    to: Option<u64>,
+    environment: Option<String>,
+    offset: Option<usize>,
+    limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Unchanged,
+    Added,
+    Removed,
+    Changed,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HeaderChange {
+    name: String,
+    kind: ChangeKind,
+    left: Option<String>,
+    right: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JsonChange {
+    path: String, // JSONPath of the changed value, e.g. $.items[0].id
+    kind: ChangeKind,
+    left: Option<serde_json::Value>,
+    right: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LineChange {
+    kind: ChangeKind,
+    text: String,
+    left_line: Option<usize>, // 1-based line numbers
+    right_line: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BodyDiff {
+    Identical,
+    Json { changes: Vec<JsonChange> },
+    Text { lines: Vec<LineChange> },
+    Binary { left_size: usize, right_size: usize },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ResponseDiff {
+    left_id: u64,
+    right_id: u64,
+    identical: bool,
+    left_status: u16,
+    right_status: u16,
+    headers: Vec<HeaderChange>, // differing headers only
+    body: BodyDiff,
+}
+
+// Loaded history, with the next id to hand out
+struct HistoryLog {
+    entries: Vec<HistoryEntry>,
+    next_id: u64,
+}
+
+impl HistoryLog {
+    fn load(path: &std::path::Path) -> Self {
+        // Unreadable lines are skipped rather than losing the whole history
+        let entries: Vec<HistoryEntry> = fs::read_to_string(path)
+            .map(|content| content.lines().filter_map(|line| serde_json::from_str(line).ok()).collect())
+            .unwrap_or_default();
+        // The id file remembers ids of deleted entries so they are never handed out again
+        let last_deleted = fs::read_to_string(path.with_file_name(HTTP_HISTORY_ID_FILE))
+            .ok()
+            .and_then(|content| content.trim().parse::<u64>().ok())
+            .unwrap_or(0);
+        let last = entries.iter().map(|entry| entry.id).max().unwrap_or(0).max(last_deleted);
+        HistoryLog { entries, next_id: last + 1 }
+    }
+}
+
+// History entries, loaded from the app data dir on first use. Recording can be turned off.
+#[derive(Default)]
+pub struct HttpHistory {
+    log: std::sync::Arc<Mutex<Option<HistoryLog>>>,
+    disabled: std::sync::atomic::AtomicBool,
+}
+
+impl HttpHistory {
+    // Runs on the blocking pool, since the first use reads the file and most calls write it
+    async fn with_entries<T: Send + 'static>(
+        &self,
+        app: &tauri::AppHandle,
+        f: impl FnOnce(&mut HistoryLog, &std::path::Path) -> Result<T, String> + Send + 'static,
+    ) -> Result<T, String> {
+        use tauri::Manager;
+        let dir = app
+            .path()
+            .app_data_dir()
+            .map_err(|e| format!("Failed to resolve app data dir: {}", e))?;
+        let path = dir.join(HTTP_HISTORY_FILE);
+        let log = self.log.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut log = log.lock().map_err(|_| "HTTP history is unavailable".to_string())?;
+            let log = log.get_or_insert_with(|| HistoryLog::load(&path));
+            f(log, &path)
+        })
+        .await
+        .map_err(|e| format!("Task execution failed: {}", e))?
+    }
+
+    pub(crate) async fn record(
+        &self,
+        app: &tauri::AppHandle,
+        request: HttpRequest,
+        response: HttpResponse,
+        environment: Option<HistoryEnvironment>,
+    ) -> Result<(), String> {
+        if self.disabled.load(std::sync::atomic::Ordering::Relaxed) {
+            return Ok(());
+        }
+        self.with_entries(app, move |log, path| {
+            let id = log.next_id;
+            log.next_id += 1;
+            let entry = history_entry(id, request, response, environment);
+            let line = serde_json::to_string(&entry).map_err(|e| format!("Failed to serialize history: {}", e))?;
+            log.entries.push(entry);
+
+            if log.entries.len() > HTTP_HISTORY_LIMIT {
+                let excess = log.entries.len() - HTTP_HISTORY_LIMIT;
+                log.entries.drain(..excess);
+                write_http_history(path, &log.entries)
+            } else {
+                use std::io::Write;
+                if let Some(dir) = path.parent() {
+                    fs::create_dir_all(dir).map_err(|e| format!("Failed to create history dir: {}", e))?;
+                }
+                let mut file = fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .map_err(|e| format!("Failed to open history: {}", e))?;
+                writeln!(file, "{}", line).map_err(|e| format!("Failed to write history: {}", e))
+            }
+        })
+        .await
+    }
+
+    async fn entry(&self, app: &tauri::AppHandle, id: u64) -> Result<HistoryEntry, String> {
+        self.with_entries(app, move |log, _| {
+            log.entries
+                .iter()
+                .find(|entry| entry.id == id)
+                .cloned()
+                .ok_or_else(|| format!("History entry {} not found", id))
+        })
+        .await
+    }
+}
+
+// Replace a credential unless it is a {{...}} reference, which is resolved at send time
+fn redact_value(value: &mut String) -> bool {
+    if value.is_empty() || value.contains("{{") {
+        return false;
+    }
+    *value = HISTORY_REDACTED.to_string();
+    true
+}
+
+// Replace the password of a URL unless it is a {{...}} reference
+fn redact_url_password(url: &mut String) -> bool {
+    let Ok(mut parsed) = reqwest::Url::parse(url) else { return false };
+    // The password is percent-encoded here, so a {{...}} reference shows as %7B%7B
+    let plain_password = parsed.password().is_some_and(|password| !password.contains("%7B%7B"));
+    if plain_password && parsed.set_password(Some(HISTORY_REDACTED)).is_ok() {
+        *url = parsed.to_string();
+        return true;
+    }
+    false
+}
+
+fn redact_headers(headers: &mut HashMap<String, String>, redacted: &mut Vec<String>) {
+    for (name, value) in headers.iter_mut() {
+        if HISTORY_SENSITIVE_HEADERS.contains(&name.to_ascii_lowercase().as_str()) && redact_value(value) {
+            redacted.push(format!("header {}", name));
+        }
+    }
+}
+
+fn redact_auth(auth: &mut RequestAuth) -> bool {
+    let mut secrets: Vec<&mut String> = Vec::new();
+    match auth {
+        RequestAuth::Basic { password, .. } => secrets.extend(password.as_mut()),
+        RequestAuth::Bearer { token } => secrets.push(token),
+        RequestAuth::ApiKey { value, .. } => secrets.push(value),
+        RequestAuth::Digest { password, .. } => secrets.push(password),
+        RequestAuth::AwsSigv4 { secret_access_key, session_token, .. } => {
+            secrets.push(secret_access_key);
+            secrets.extend(session_token.as_mut());
+        }
+        RequestAuth::Oauth1 { consumer_secret, token_secret, .. } => {
+            secrets.push(consumer_secret);
+            secrets.extend(token_secret.as_mut());
+        }
+    }
+    secrets.into_iter().fold(false, |redacted, secret| redact_value(secret) | redacted)
+}
+
+// Stored form of an exchange: credentials redacted and one capped copy of the body
+fn history_entry(
+    id: u64,
+    mut request: HttpRequest,
+    mut response: HttpResponse,
+    mut environment: Option<HistoryEnvironment>,
+) -> HistoryEntry {
+    let mut redacted = Vec::new();
+    if redact_url_password(&mut request.url) {
+        redacted.push("URL password".to_string());
+    }
+    redact_headers(&mut request.headers, &mut redacted);
+    if request.auth.as_mut().is_some_and(redact_auth) {
+        redacted.push("auth".to_string());
+    }
+    if let Some(options) = request.options.as_mut() {
+        if options.proxy_url.as_mut().is_some_and(redact_url_password) {
+            redacted.push("proxy password".to_string());
+        }
+        // The client private key is dropped rather than stored
+        let plain_key = options.client_key_pem.as_ref().is_some_and(|pem| !pem.trim().is_empty() && !pem.contains("{{"));
+        if plain_key {
+            options.client_key_pem = None;
+            redacted.push("client key".to_string());
+        }
+    }
+    // Response cookies are not part of the request, so they do not count as redactions
+    redact_headers(&mut response.headers, &mut Vec::new());
+    for hop in &mut response.redirects {
+        redact_headers(&mut hop.headers, &mut Vec::new());
+    }
+    if let Some(environment) = environment.as_mut() {
+        for value in environment.variables.values_mut() {
+            redact_value(value);
+        }
+    }
+
+    response.wire_body_base64 = None;
+    let body = if response.is_text { &mut response.body } else { &mut response.body_base64 };
+    let mut body_truncated = false;
+    if body.len() > HTTP_HISTORY_BODY_LIMIT {
+        // Cut on a char boundary for text and on a whole base64 quantum for binary bodies
+        let mut end = HTTP_HISTORY_BODY_LIMIT - HTTP_HISTORY_BODY_LIMIT % 4;
+        while !body.is_char_boundary(end) {
+            end -= 1;
+        }
+        body.truncate(end);
+        body_truncated = true;
+    }
+    if response.is_text {
+        response.body_base64 = String::new();
+    } else {
+        response.body = String::new();
+    }
+
+    HistoryEntry {
+        id,
+        timestamp: unix_now_ms(),
+        request,
+        response,
+        environment,
+        redacted,
+        body_truncated,
+    }
+}
+
+// Rewrite the whole history file through a temporary file
+fn write_http_history(path: &std::path::Path, entries: &[HistoryEntry]) -> Result<(), String> {
+    let mut content = String::new();
+    for entry in entries {
+        content.push_str(&serde_json::to_string(entry).map_err(|e| format!("Failed to serialize history: {}", e))?);
+        content.push('\n');
+    }
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("Failed to create history dir: {}", e))?;
+    }
+    let temp = path.with_extension("jsonl.tmp");
+    fs::write(&temp, content).map_err(|e| format!("Failed to write history: {}", e))?;
+    fs::rename(&temp, path).map_err(|e| format!("Failed to write history: {}", e))
+}
+
+fn history_matches(entry: &HistoryEntry, query: &HistoryQuery) -> bool {
+    let status = entry.response.status;
+    query
+        .url
+        .as_ref()
+        .is_none_or(|url| entry.request.url.to_lowercase().contains(&url.to_lowercase()))
+        && query.method.as_ref().is_none_or(|method| entry.request.method.eq_ignore_ascii_case(method))
+        && query.status.is_none_or(|wanted| status == wanted)
+        && query.status_min.is_none_or(|min| status >= min)
+        && query.status_max.is_none_or(|max| status <= max)
+        && query.from.is_none_or(|from| entry.timestamp >= from)
+        && query.to.is_none_or(|to| entry.timestamp <= to)
+        && query.environment.as_ref().is_none_or(|name| {
+            entry.environment.as_ref().and_then(|environment| environment.name.as_ref()) == Some(name)
+        })
+}
+
+fn json_path_child(path: &str, key: &str) -> String {
+    let plain = !key.is_empty()
+        && !key.starts_with(|c: char| c.is_ascii_digit())
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if plain {
+        format!("{}.{}", path, key)
+    } else {
+        format!("{}['{}']", path, key.replace('\\', "\\\\").replace('\'', "\\'"))
+    }
+}
+
+fn diff_json(path: String, left: &serde_json::Value, right: &serde_json::Value, changes: &mut Vec<JsonChange>) {
+    use serde_json::Value;
+    match (left, right) {
+        (Value::Object(left), Value::Object(right)) => {
+            let mut keys: Vec<&String> = left.keys().chain(right.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                let child = json_path_child(&path, key);
+                match (left.get(key), right.get(key)) {
+                    (Some(l), Some(r)) => diff_json(child, l, r, changes),
+                    (l, r) => changes.push(JsonChange {
+                        path: child,
+                        kind: if l.is_some() { ChangeKind::Removed } else { ChangeKind::Added },
+                        left: l.cloned(),
+                        right: r.cloned(),
+                    }),
+                }
+            }
+        }
+        (Value::Array(left), Value::Array(right)) => {
+            for index in 0..left.len().max(right.len()) {
+                let child = format!("{}[{}]", path, index);
+                match (left.get(index), right.get(index)) {
+                    (Some(l), Some(r)) => diff_json(child, l, r, changes),
+                    (l, r) => changes.push(JsonChange {
+                        path: child,
+                        kind: if l.is_some() { ChangeKind::Removed } else { ChangeKind::Added },
+                        left: l.cloned(),
+                        right: r.cloned(),
+                    }),
+                }
+            }
+        }
+        (left, right) if !json_values_equal(left, right) => changes.push(JsonChange {
+            path,
+            kind: ChangeKind::Changed,
+            left: Some(left.clone()),
+            right: Some(right.clone()),
+        }),
+        _ => {}
+    }
+}
+
+// Line diff over the longest common subsequence of the lines that differ
+fn diff_lines(left: &str, right: &str) -> Vec<LineChange> {
+    let left: Vec<&str> = left.lines().collect();
+    let right: Vec<&str> = right.lines().collect();
+    let prefix = left.iter().zip(&right).take_while(|(l, r)| l == r).count();
+    let suffix = left[prefix..]
+        .iter()
+        .rev()
+        .zip(right[prefix..].iter().rev())
+        .take_while(|(l, r)| l == r)
+        .count();
+    let (left_middle, right_middle) = (&left[prefix..left.len() - suffix], &right[prefix..right.len() - suffix]);
+
+    let line = |kind, text: &str, left_line, right_line| LineChange { kind, text: text.to_string(), left_line, right_line };
+    let mut lines: Vec<LineChange> = left[..prefix]
+        .iter()
+        .enumerate()
+        .map(|(i, text)| line(ChangeKind::Unchanged, text, Some(i + 1), Some(i + 1)))
+        .collect();
+
+    let (n, m) = (left_middle.len(), right_middle.len());
+    if n * m > 4_000_000 {
+        // Too large to align: show the differing block as removed then added
+        lines.extend(left_middle.iter().enumerate().map(|(i, text)| line(ChangeKind::Removed, text, Some(prefix + i + 1), None)));
+        lines.extend(right_middle.iter().enumerate().map(|(j, text)| line(ChangeKind::Added, text, None, Some(prefix + j + 1))));
+    } else {
+        // lengths[i][j] = LCS length of left_middle[i..] and right_middle[j..]
+        let mut lengths = vec![vec![0u32; m + 1]; n + 1];
+        for i in (0..n).rev() {
+            for j in (0..m).rev() {
+                lengths[i][j] = if left_middle[i] == right_middle[j] {
+                    lengths[i + 1][j + 1] + 1
+                } else {
+                    lengths[i + 1][j].max(lengths[i][j + 1])
+                };
+            }
+        }
+        let (mut i, mut j) = (0, 0);
+        while i < n || j < m {
+            if i < n && j < m && left_middle[i] == right_middle[j] {
+                lines.push(line(ChangeKind::Unchanged, left_middle[i], Some(prefix + i + 1), Some(prefix + j + 1)));
+                i += 1;
+                j += 1;
+            } else if j == m || (i < n && lengths[i + 1][j] >= lengths[i][j + 1]) {
+                lines.push(line(ChangeKind::Removed, left_middle[i], Some(prefix + i + 1), None));
+                i += 1;
+            } else {
+                lines.push(line(ChangeKind::Added, right_middle[j], None, Some(prefix + j + 1)));
+                j += 1;
+            }
+        }
+    }
+
+    lines.extend(left[left.len() - suffix..].iter().enumerate().map(|(k, text)| {
+        line(ChangeKind::Unchanged, text, Some(left.len() - suffix + k + 1), Some(right.len() - suffix + k + 1))
+    }));
+    lines
+}
+
+fn diff_responses(left_id: u64, left: &HttpResponse, right_id: u64, right: &HttpResponse) -> ResponseDiff {
+    let lower = |headers: &HashMap<String, String>| -> std::collections::BTreeMap<String, String> {
+        headers.iter().map(|(name, value)| (name.to_ascii_lowercase(), value.clone())).collect()
+    };
+    let (left_headers, right_headers) = (lower(&left.headers), lower(&right.headers));
+    let mut names: Vec<&String> = left_headers.keys().chain(right_headers.keys()).collect();
+    names.sort();
+    names.dedup();
+    let headers: Vec<HeaderChange> = names
+        .into_iter()
+        .filter_map(|name| {
+            let (l, r) = (left_headers.get(name), right_headers.get(name));
+            let kind = match (l, r) {
+                (Some(l), Some(r)) if l == r => return None,
+                (Some(_), Some(_)) => ChangeKind::Changed,
+                (Some(_), None) => ChangeKind::Removed,
+                _ => ChangeKind::Added,
+            };
+            Some(HeaderChange { name: name.clone(), kind, left: l.cloned(), right: r.cloned() })
+        })
+        .collect();
+
+    let body = if left.body_base64 == right.body_base64 && left.body == right.body {
+        BodyDiff::Identical
+    } else if !left.is_text || !right.is_text {
+        BodyDiff::Binary { left_size: left.body_size, right_size: right.body_size }
+    } else {
+        match (
+            serde_json::from_str::<serde_json::Value>(&left.body),
+            serde_json::from_str::<serde_json::Value>(&right.body),
+        ) {
+            (Ok(l), Ok(r)) => {
+                let mut changes = Vec::new();
+                diff_json("$".to_string(), &l, &r, &mut changes);
+                // Formatting-only differences leave nothing to report
+                if changes.is_empty() { BodyDiff::Identical } else { BodyDiff::Json { changes } }
+            }
+            _ => BodyDiff::Text { lines: diff_lines(&left.body, &right.body) },
+        }
+    };
+
+    ResponseDiff {
+        left_id,
+        right_id,
+        identical: left.status == right.status && headers.is_empty() && matches!(body, BodyDiff::Identical),
+        left_status: left.status,
+        right_status: right.status,
+        headers,
+        body,
+    }
+}
+
+// List history entries matching the query, newest first
+#[tauri::command]
+pub async fn query_http_history(
+    app: tauri::AppHandle,
+    history: tauri::State<'_, HttpHistory>,
+    query: Option<HistoryQuery>,
+) -> Result<Vec<HistorySummary>, String> {
+    let query = query.unwrap_or_default();
+    history.with_entries(&app, move |log, _| {
+        Ok(log
+            .entries
+            .iter()
+            .rev()
+            .filter(|entry| history_matches(entry, &query))
+            .skip(query.offset.unwrap_or(0))
+            .take(query.limit.unwrap_or(usize::MAX))
+            .map(|entry| HistorySummary {
+                id: entry.id,
+                timestamp: entry.timestamp,
+                method: entry.request.method.clone(),
+                url: entry.request.url.clone(),
+                status: entry.response.status,
+                error: entry.response.error.clone(),
+                total_ms: entry.response.timing.as_ref().map(|timing| timing.total_ms),
+                body_size: entry.response.body_size,
+                environment: entry.environment.as_ref().and_then(|environment| environment.name.clone()),
+            })
+            .collect())
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn get_http_history_entry(
+    app: tauri::AppHandle,
+    history: tauri::State<'_, HttpHistory>,
+    id: u64,
+) -> Result<HistoryEntry, String> {
+    history.entry(&app, id).await
+}
+
+// Turn recording of new exchanges on or off; existing entries stay available
+#[tauri::command]
+pub fn set_http_history_enabled(history: tauri::State<'_, HttpHistory>, enabled: bool) {
+    history.disabled.store(!enabled, std::sync::atomic::Ordering::Relaxed);
+}
+
+// Send a stored request again; the new exchange is recorded as its own entry
+#[tauri::command]
+pub async fn rerun_http_history(
+    app: tauri::AppHandle,
+    sessions: tauri::State<'_, HttpSessions>,
+    history: tauri::State<'_, HttpHistory>,
+    secrets: tauri::State<'_, SecretStore>,
+    id: u64,
+) -> Result<HttpResponse, String> {
+    let entry = history.entry(&app, id).await?;
+    if !entry.redacted.is_empty() {
+        return Err(format!(
+            "History entry {} was stored without its credentials ({}); send it again from the request editor",
+            id,
+            entry.redacted.join(", ")
+        ));
+    }
+    let options = entry.request.options.clone().unwrap_or_default();
+    let session = entry.request.session.as_deref().unwrap_or(DEFAULT_HTTP_SESSION);
+    let client = sessions.client(session, &options)?;
+    let mask = secrets.mask(&entry.request);
+    let mut response = execute_http_request(&client, secrets.resolve(entry.request.clone())?)
+        .await
+        .map_err(|e| mask.apply(&e))?;
+    mask.apply_response(&mut response);
+    response.history_error = history.record(&app, entry.request, response.clone(), entry.environment).await.err();
+    Ok(response)
+}
+
+#[tauri::command]
+pub async fn diff_http_history(
+    app: tauri::AppHandle,
+    history: tauri::State<'_, HttpHistory>,
+    left_id: u64,
+    right_id: u64,
+) -> Result<ResponseDiff, String> {
+    let left = history.entry(&app, left_id).await?;
+    let right = history.entry(&app, right_id).await?;
+    Ok(diff_responses(left_id, &left.response, right_id, &right.response))
+}
+
+// Delete the given entries, or the whole history when no ids are given
+#[tauri::command]
+pub async fn delete_http_history(
+    app: tauri::AppHandle,
+    history: tauri::State<'_, HttpHistory>,
+    ids: Option<Vec<u64>>,
+) -> Result<usize, String> {
+    history.with_entries(&app, move |log, path| {
+        let before = log.entries.len();
+        match ids {
+            Some(ids) => log.entries.retain(|entry| !ids.contains(&entry.id)),
+            None => log.entries.clear(),
+        }
+        // Save the last id handed out first, so deleted ids are not reused after a restart
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| format!("Failed to create history dir: {}", e))?;
+        }
+        fs::write(path.with_file_name(HTTP_HISTORY_ID_FILE), (log.next_id - 1).to_string())
+            .map_err(|e| format!("Failed to write history: {}", e))?;
+        write_http_history(path, &log.entries)?;
+        Ok(before - log.entries.len())
+    })
+    .await
+}
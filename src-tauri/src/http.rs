@@ -0,0 +1,2362 @@
+//! HTTP client: requests, sessions, cookies and streamed responses
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use base64::{Engine as _, engine::general_purpose};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use crate::assertions::{AssertionResult, evaluate_assertions, ResponseAssertion};
+use crate::history::{HistoryEnvironment, HttpHistory};
+use crate::secrets::{SecretMask, SecretStore};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HttpRequest {
+    pub(crate) url: String,
+    pub(crate) method: String,
+    pub(crate) headers: HashMap<String, String>,
+    pub(crate) body: Option<RequestBody>,
+    pub(crate) options: Option<HttpRequestOptions>,
+    pub(crate) session: Option<String>, // named client session; "default" when omitted
+    pub(crate) auth: Option<RequestAuth>,
+}
+
+// Per-request client settings
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct HttpRequestOptions {
+    pub(crate) timeout_ms: Option<u64>,
+    pub(crate) max_redirects: Option<usize>, // 0 disables redirect following
+    pub(crate) proxy_url: Option<String>,    // http://, https://, socks5:// or socks5h://
+    pub(crate) insecure: Option<bool>,       // skip certificate and hostname verification
+    pub(crate) ca_cert_pem: Option<String>,
+    client_cert_pem: Option<String>,
+    pub(crate) client_key_pem: Option<String>,
+    pub(crate) capture_redirects: Option<bool>, // follow redirects manually and report every hop
+    pub(crate) protocol: Option<HttpProtocol>,
+    // true: ask for gzip, deflate and br and decode them; false: neither; unset: decode
+    // encoded responses without sending Accept-Encoding
+    pub(crate) decompress: Option<bool>,
+}
+
+// HTTP version used by http_request
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum HttpProtocol {
+    #[default]
+    Auto, // HTTP/2 when the server selects h2 through ALPN, HTTP/1.1 otherwise
+    Http1,
+    Http2, // prior knowledge on cleartext, h2 as the only ALPN offer over TLS
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MultipartEntry {
+    pub(crate) key: String,
+    pub(crate) value: String, // text value, or base64 file content when no path is given
+    pub(crate) r#type: String, // "text" or "file"
+    pub(crate) filename: Option<String>,
+    pub(crate) path: Option<String>, // file streamed from disk
+    pub(crate) content_type: Option<String>, // guessed from the file extension when omitted
+    pub(crate) headers: Option<HashMap<String, String>>,
+}
+
+// Request body, tagged by "type"
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RequestBody {
+    Text {
+        content: String,
+        content_type: Option<String>,
+    },
+    Json {
+        content: String,
+    },
+    Urlencoded {
+        fields: Vec<FormField>,
+    },
+    Binary {
+        path: Option<String>,   // file streamed from disk
+        base64: Option<String>, // inline content when no path is given
+        content_type: Option<String>,
+    },
+    Multipart {
+        entries: Vec<MultipartEntry>,
+    },
+    Graphql {
+        query: String,
+        variables: Option<serde_json::Value>,
+        operation_name: Option<String>,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FormField {
+    pub(crate) key: String,
+    pub(crate) value: String,
+}
+
+// Authentication applied by the backend right before each request is sent
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RequestAuth {
+    Basic {
+        username: String,
+        password: Option<String>,
+    },
+    Bearer {
+        token: String,
+    },
+    ApiKey {
+        key: String,
+        value: String,
+        #[serde(default)]
+        location: ApiKeyLocation,
+    },
+    Digest {
+        username: String,
+        password: String,
+    },
+    AwsSigv4 {
+        access_key_id: String,
+        secret_access_key: String,
+        session_token: Option<String>,
+        region: String,
+        service: String,
+    },
+    Oauth1 {
+        consumer_key: String,
+        consumer_secret: String,
+        token: Option<String>,
+        token_secret: Option<String>,
+        realm: Option<String>,
+        callback: Option<String>,
+        verifier: Option<String>,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiKeyLocation {
+    #[default]
+    Header,
+    Query,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HttpResponse {
+    pub(crate) status: u16,
+    pub(crate) headers: HashMap<String, String>,
+    pub(crate) body: String, // decoded text, empty when the body is binary
+    pub(crate) body_base64: String,
+    pub(crate) body_size: usize,
+    content_encoding: Option<String>,
+    wire_size: usize,                 // body size as transferred, before content decoding
+    pub(crate) wire_body_base64: Option<String>, // body as transferred, when it was decoded
+    content_type: Option<String>,
+    charset: Option<String>,
+    pub(crate) is_text: bool,
+    pub(crate) url: String, // final URL after redirects
+    pub(crate) redirects: Vec<RedirectHop>,
+    pub(crate) timing: Option<HttpTiming>, // timing of the final request
+    local_addr: Option<String>,
+    remote_addr: Option<String>,
+    http_version: Option<String>,  // e.g. "HTTP/1.1" or "HTTP/2"
+    alpn_protocol: Option<String>, // "h2" or "http/1.1" over TLS; a server without ALPN shows as http/1.1
+    pub(crate) error: Option<String>,
+    pub(crate) assertions: Vec<AssertionResult>, // results of the assertions sent with the request
+    pub(crate) history_error: Option<String>,    // set when the exchange could not be saved to history
+}
+
+// One redirect response on the way to the final response
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RedirectHop {
+    pub(crate) url: String,
+    status: u16,
+    pub(crate) headers: HashMap<String, String>,
+    pub(crate) location: String,
+    elapsed_ms: f64,
+}
+
+// Request phase durations in milliseconds; connection phases are None on a reused connection
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct HttpTiming {
+    dns_ms: Option<f64>,
+    tcp_connect_ms: Option<f64>,
+    tls_handshake_ms: Option<f64>,
+    ttfb_ms: f64,
+    download_ms: f64,
+    pub(crate) total_ms: f64,
+    connection_reused: bool,
+}
+
+impl HttpResponse {
+    fn from_error(error: String) -> Self {
+        HttpResponse {
+            status: 0,
+            headers: HashMap::new(),
+            body: String::new(),
+            body_base64: String::new(),
+            body_size: 0,
+            content_encoding: None,
+            wire_size: 0,
+            wire_body_base64: None,
+            content_type: None,
+            charset: None,
+            is_text: true,
+            url: String::new(),
+            redirects: Vec::new(),
+            timing: None,
+            local_addr: None,
+            remote_addr: None,
+            http_version: None,
+            alpn_protocol: None,
+            error: Some(error),
+            assertions: Vec::new(),
+            history_error: None,
+        }
+    }
+}
+
+// Connection phase timestamps collected while a request is in flight
+#[derive(Debug, Default)]
+struct ConnectionTrace {
+    dns_start: Option<std::time::Instant>,
+    dns_end: Option<std::time::Instant>,
+    connect_start: Option<std::time::Instant>,
+    tls_start: Option<std::time::Instant>,
+    connect_end: Option<std::time::Instant>,
+    negotiated_h2: Option<bool>, // whether a new connection selected h2 through ALPN
+}
+
+tokio::task_local! {
+    // Trace of the request being sent on the current task; the resolver, connector layer
+    // and TLS session store below record into it
+    static HTTP_TRACE: std::sync::Arc<Mutex<ConnectionTrace>>;
+}
+
+fn trace_mark(mark: impl FnOnce(&mut ConnectionTrace)) {
+    let _ = HTTP_TRACE.try_with(|trace| {
+        if let Ok(mut trace) = trace.lock() {
+            mark(&mut trace);
+        }
+    });
+}
+
+fn ms_between(start: std::time::Instant, end: std::time::Instant) -> f64 {
+    end.saturating_duration_since(start).as_secs_f64() * 1000.0
+}
+
+impl ConnectionTrace {
+    // Turn the collected timestamps into phase durations
+    fn timing(&self, started: std::time::Instant, headers_at: std::time::Instant, finished: std::time::Instant) -> HttpTiming {
+        let dns_ms = self.dns_start.zip(self.dns_end).map(|(start, end)| ms_between(start, end));
+        let (tcp_connect_ms, tls_handshake_ms) = match self.connect_end {
+            Some(connect_end) => {
+                let tcp_start = self.dns_end.or(self.connect_start).unwrap_or(started);
+                match self.tls_start {
+                    Some(tls_start) => (Some(ms_between(tcp_start, tls_start)), Some(ms_between(tls_start, connect_end))),
+                    None => (Some(ms_between(tcp_start, connect_end)), None),
+                }
+            }
+            None => (None, None),
+        };
+
+        HttpTiming {
+            dns_ms,
+            tcp_connect_ms,
+            tls_handshake_ms,
+            ttfb_ms: ms_between(self.connect_end.unwrap_or(started), headers_at),
+            download_ms: ms_between(headers_at, finished),
+            total_ms: ms_between(started, finished),
+            connection_reused: self.connect_end.is_none(),
+        }
+    }
+}
+
+// DNS resolver that records how long the lookup took
+struct TracingResolver;
+
+impl reqwest::dns::Resolve for TracingResolver {
+    fn resolve(&self, name: reqwest::dns::Name) -> reqwest::dns::Resolving {
+        Box::pin(async move {
+            trace_mark(|trace| trace.dns_start = Some(std::time::Instant::now()));
+            let addrs = tokio::net::lookup_host((name.as_str(), 0)).await?;
+            let addrs: Vec<std::net::SocketAddr> = addrs.collect();
+            trace_mark(|trace| trace.dns_end = Some(std::time::Instant::now()));
+            Ok(Box::new(addrs.into_iter()) as reqwest::dns::Addrs)
+        })
+    }
+}
+
+// Connector layer marking when connection establishment starts and finishes
+#[derive(Clone)]
+struct TracingConnectLayer;
+
+impl<S> tower::Layer<S> for TracingConnectLayer {
+    type Service = TracingConnect<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        TracingConnect(inner)
+    }
+}
+
+#[derive(Clone)]
+struct TracingConnect<S>(S);
+
+impl<S, Req> tower::Service<Req> for TracingConnect<S>
+where
+    S: tower::Service<Req>,
+    S::Response: hyper_util::client::legacy::connect::Connection,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = std::pin::Pin<Box<dyn std::future::Future<Output = Result<S::Response, S::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut std::task::Context<'_>) -> std::task::Poll<Result<(), Self::Error>> {
+        self.0.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Req) -> Self::Future {
+        let connecting = self.0.call(req);
+        Box::pin(async move {
+            trace_mark(|trace| trace.connect_start = Some(std::time::Instant::now()));
+            let result = connecting.await;
+            let negotiated_h2 = result
+                .as_ref()
+                .ok()
+                .map(|conn| hyper_util::client::legacy::connect::Connection::connected(conn).is_negotiated_h2());
+            trace_mark(|trace| {
+                trace.connect_end = Some(std::time::Instant::now());
+                trace.negotiated_h2 = negotiated_h2;
+            });
+            result
+        })
+    }
+}
+
+// rustls looks up the key exchange hint while building the ClientHello, right after the
+// TCP connection is up, so this session store doubles as the TLS start marker. This relies
+// on the ClientHello path of rustls 0.23; if a later release stops calling kx_hint there,
+// tls_start stays unset and the handshake is reported as part of tcp_connect_ms instead
+#[derive(Debug)]
+struct TracingSessionStore(rustls::client::ClientSessionMemoryCache);
+
+impl rustls::client::ClientSessionStore for TracingSessionStore {
+    fn set_kx_hint(&self, server_name: rustls::pki_types::ServerName<'static>, group: rustls::NamedGroup) {
+        self.0.set_kx_hint(server_name, group)
+    }
+
+    fn kx_hint(&self, server_name: &rustls::pki_types::ServerName<'_>) -> Option<rustls::NamedGroup> {
+        trace_mark(|trace| trace.tls_start = Some(std::time::Instant::now()));
+        self.0.kx_hint(server_name)
+    }
+
+    fn set_tls12_session(
+        &self,
+        server_name: rustls::pki_types::ServerName<'static>,
+        value: rustls::client::Tls12ClientSessionValue,
+    ) {
+        self.0.set_tls12_session(server_name, value)
+    }
+
+    fn tls12_session(
+        &self,
+        server_name: &rustls::pki_types::ServerName<'_>,
+    ) -> Option<rustls::client::Tls12ClientSessionValue> {
+        self.0.tls12_session(server_name)
+    }
+
+    fn remove_tls12_session(&self, server_name: &rustls::pki_types::ServerName<'static>) {
+        self.0.remove_tls12_session(server_name)
+    }
+
+    fn insert_tls13_ticket(
+        &self,
+        server_name: rustls::pki_types::ServerName<'static>,
+        value: rustls::client::Tls13ClientSessionValue,
+    ) {
+        self.0.insert_tls13_ticket(server_name, value)
+    }
+
+    fn take_tls13_ticket(
+        &self,
+        server_name: &rustls::pki_types::ServerName<'static>,
+    ) -> Option<rustls::client::Tls13ClientSessionValue> {
+        self.0.take_tls13_ticket(server_name)
+    }
+}
+
+// Certificate verifier that accepts any server certificate (insecure mode)
+#[derive(Debug)]
+struct InsecureCertVerifier(std::sync::Arc<rustls::crypto::CryptoProvider>);
+
+impl rustls::client::danger::ServerCertVerifier for InsecureCertVerifier {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &rustls::pki_types::CertificateDer<'_>,
+        _intermediates: &[rustls::pki_types::CertificateDer<'_>],
+        _server_name: &rustls::pki_types::ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: rustls::pki_types::UnixTime,
+    ) -> Result<rustls::client::danger::ServerCertVerified, rustls::Error> {
+        Ok(rustls::client::danger::ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &rustls::pki_types::CertificateDer<'_>,
+        dss: &rustls::DigitallySignedStruct,
+    ) -> Result<rustls::client::danger::HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls12_signature(message, cert, dss, &self.0.signature_verification_algorithms)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &rustls::pki_types::CertificateDer<'_>,
+        dss: &rustls::DigitallySignedStruct,
+    ) -> Result<rustls::client::danger::HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls13_signature(message, cert, dss, &self.0.signature_verification_algorithms)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<rustls::SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+fn parse_pem_certificates(pem: &str, what: &str) -> Result<Vec<rustls::pki_types::CertificateDer<'static>>, String> {
+    let certs = rustls_pemfile::certs(&mut pem.as_bytes())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Invalid {} PEM: {}", what, e))?;
+    if certs.is_empty() {
+        return Err(format!("No certificate found in {} PEM", what));
+    }
+    Ok(certs)
+}
+
+// Platform root certificates, loaded once; reading the system store is slow on some platforms
+fn native_root_store() -> &'static rustls::RootCertStore {
+    static ROOTS: std::sync::OnceLock<rustls::RootCertStore> = std::sync::OnceLock::new();
+    ROOTS.get_or_init(|| {
+        let mut roots = rustls::RootCertStore::empty();
+        roots.add_parsable_certificates(rustls_native_certs::load_native_certs().certs);
+        roots
+    })
+}
+
+// TLS configuration for http_request: system roots plus any custom CA, optional client certificate
+pub(crate) fn build_http_tls_config(options: &HttpRequestOptions) -> Result<rustls::ClientConfig, String> {
+    let mut roots = native_root_store().clone();
+    if let Some(ca_pem) = options.ca_cert_pem.as_deref().filter(|pem| !pem.trim().is_empty()) {
+        for cert in parse_pem_certificates(ca_pem, "CA certificate")? {
+            roots
+                .add(cert)
+                .map_err(|e| format!("Invalid CA certificate: {}", e))?;
+        }
+    }
+
+    let provider = std::sync::Arc::new(rustls::crypto::aws_lc_rs::default_provider());
+    let builder = rustls::ClientConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()
+        .map_err(|e| format!("Failed to configure TLS: {}", e))?
+        .with_root_certificates(roots);
+
+    let client_cert = options.client_cert_pem.as_deref().filter(|pem| !pem.trim().is_empty());
+    let client_key = options.client_key_pem.as_deref().filter(|pem| !pem.trim().is_empty());
+    let mut config = match (client_cert, client_key) {
+        (Some(cert_pem), Some(key_pem)) => {
+            let certs = parse_pem_certificates(cert_pem, "client certificate")?;
+            let key = rustls_pemfile::private_key(&mut key_pem.as_bytes())
+                .map_err(|e| format!("Invalid client key PEM: {}", e))?
+                .ok_or_else(|| "No private key found in client key PEM".to_string())?;
+            builder
+                .with_client_auth_cert(certs, key)
+                .map_err(|e| format!("Invalid client certificate: {}", e))?
+        }
+        (None, None) => builder.with_no_client_auth(),
+        _ => return Err("Client certificate and key must be provided together".to_string()),
+    };
+
+    if options.insecure.unwrap_or(false) {
+        config
+            .dangerous()
+            .set_certificate_verifier(std::sync::Arc::new(InsecureCertVerifier(provider)));
+    }
+
+    config.alpn_protocols = match options.protocol.unwrap_or_default() {
+        HttpProtocol::Auto => vec![b"h2".to_vec(), b"http/1.1".to_vec()],
+        HttpProtocol::Http1 => vec![b"http/1.1".to_vec()],
+        HttpProtocol::Http2 => vec![b"h2".to_vec()],
+    };
+    config.resumption = rustls::client::Resumption::store(std::sync::Arc::new(TracingSessionStore(
+        rustls::client::ClientSessionMemoryCache::new(256),
+    )));
+    Ok(config)
+}
+
+// Client used by http_request, instrumented for the timing breakdown. It runs on rustls with
+// the platform root store rather than reqwest's default native-tls backend, since the TLS
+// layer has to be ours to load custom CAs, client certificates and record the handshake
+pub(crate) fn build_http_client(
+    options: &HttpRequestOptions,
+    cookies: Option<std::sync::Arc<reqwest_cookie_store::CookieStoreMutex>>,
+) -> Result<reqwest::Client, String> {
+    let mut builder = reqwest::Client::builder()
+        .use_preconfigured_tls(build_http_tls_config(options)?)
+        .dns_resolver(std::sync::Arc::new(TracingResolver))
+        .connector_layer(TracingConnectLayer);
+
+    if let Some(cookies) = cookies {
+        builder = builder.cookie_provider(cookies);
+    }
+
+    if let Some(timeout_ms) = options.timeout_ms.filter(|ms| *ms > 0) {
+        builder = builder.timeout(std::time::Duration::from_millis(timeout_ms));
+    }
+    match options.protocol.unwrap_or_default() {
+        HttpProtocol::Auto => {}
+        HttpProtocol::Http1 => builder = builder.http1_only(),
+        HttpProtocol::Http2 => builder = builder.http2_prior_knowledge(),
+    }
+    if options.capture_redirects.unwrap_or(false) {
+        // http_request follows the redirects itself to record each hop
+        builder = builder.redirect(reqwest::redirect::Policy::none());
+    } else if let Some(max_redirects) = options.max_redirects {
+        builder = builder.redirect(if max_redirects == 0 {
+            reqwest::redirect::Policy::none()
+        } else {
+            reqwest::redirect::Policy::limited(max_redirects)
+        });
+    }
+    if let Some(proxy_url) = options.proxy_url.as_deref().map(str::trim).filter(|url| !url.is_empty()) {
+        let proxy = reqwest::Proxy::all(proxy_url).map_err(|e| format!("Invalid proxy URL: {}", e))?;
+        builder = builder.proxy(proxy);
+    }
+
+    builder
+        .build()
+        .map_err(|e| format!("Failed to build HTTP client: {}", e))
+}
+
+pub(crate) fn header_map_to_hash_map(header_map: &reqwest::header::HeaderMap) -> HashMap<String, String> {
+    let mut headers = HashMap::new();
+    for (key, value) in header_map {
+        if let Ok(value_str) = value.to_str() {
+            headers.insert(key.to_string(), value_str.to_string());
+        }
+    }
+    headers
+}
+
+fn http_version_name(version: reqwest::Version) -> String {
+    match version {
+        reqwest::Version::HTTP_09 => "HTTP/0.9",
+        reqwest::Version::HTTP_10 => "HTTP/1.0",
+        reqwest::Version::HTTP_11 => "HTTP/1.1",
+        reqwest::Version::HTTP_2 => "HTTP/2",
+        reqwest::Version::HTTP_3 => "HTTP/3",
+        _ => "unknown",
+    }
+    .to_string()
+}
+
+pub(crate) const DEFAULT_HTTP_SESSION: &str = "default";
+
+// A named client session: a pooled client plus the cookie jar shared by its requests
+struct HttpSession {
+    cookies: std::sync::Arc<reqwest_cookie_store::CookieStoreMutex>,
+    client: Option<(String, reqwest::Client)>, // client and the options it was built with
+}
+
+// Managed state holding the http_request client sessions
+#[derive(Default)]
+pub struct HttpSessions(Mutex<HashMap<String, HttpSession>>);
+
+impl HttpSessions {
+    // Pooled client of a session, rebuilt (keeping the cookie jar) when the options change
+    pub(crate) fn client(&self, name: &str, options: &HttpRequestOptions) -> Result<reqwest::Client, String> {
+        let options_key = serde_json::to_string(options).map_err(|e| e.to_string())?;
+        let cookies = self.cookies(name, true)?;
+        let mut sessions = self.0.lock().map_err(|_| "HTTP session state is unavailable".to_string())?;
+        let session = sessions.entry(name.to_string()).or_insert_with(|| HttpSession {
+            cookies,
+            client: None,
+        });
+
+        if let Some((built_for, client)) = &session.client {
+            if *built_for == options_key {
+                return Ok(client.clone());
+            }
+        }
+
+        let client = build_http_client(options, Some(session.cookies.clone()))?;
+        session.client = Some((options_key, client.clone()));
+        Ok(client)
+    }
+
+    // Cookie jar of a session, creating an empty session when asked to
+    fn cookies(
+        &self,
+        name: &str,
+        create: bool,
+    ) -> Result<std::sync::Arc<reqwest_cookie_store::CookieStoreMutex>, String> {
+        let mut sessions = self.0.lock().map_err(|_| "HTTP session state is unavailable".to_string())?;
+        if create {
+            let session = sessions.entry(name.to_string()).or_insert_with(|| HttpSession {
+                cookies: std::sync::Arc::new(reqwest_cookie_store::CookieStoreMutex::default()),
+                client: None,
+            });
+            return Ok(session.cookies.clone());
+        }
+        sessions
+            .get(name)
+            .map(|session| session.cookies.clone())
+            .ok_or_else(|| format!("HTTP session not found: {}", name))
+    }
+
+    fn with_cookies<T>(
+        &self,
+        name: &str,
+        create: bool,
+        f: impl FnOnce(&mut reqwest_cookie_store::CookieStore) -> T,
+    ) -> Result<T, String> {
+        let cookies = self.cookies(name, create)?;
+        let mut store = cookies.lock().map_err(|_| "Cookie store is unavailable".to_string())?;
+        Ok(f(&mut store))
+    }
+}
+
+// The default session always exists for cookie commands, empty until its first request
+fn creates_on_lookup(session: &str) -> bool {
+    session == DEFAULT_HTTP_SESSION
+}
+
+// Copy of a request without its body
+fn request_head(request: &reqwest::Request) -> reqwest::Request {
+    let mut head = reqwest::Request::new(request.method().clone(), request.url().clone());
+    *head.headers_mut() = request.headers().clone();
+    *head.timeout_mut() = request.timeout().copied();
+    head
+}
+
+// Build the follow-up request for a redirect response, mirroring browser semantics:
+// 303 (and 301/302 after POST) switch to GET without a body, 307/308 replay the request
+fn next_redirect_request(
+    previous: &reqwest::Request,
+    replay: Option<reqwest::Request>,
+    status: reqwest::StatusCode,
+    location: &str,
+) -> Result<reqwest::Request, String> {
+    let url = previous
+        .url()
+        .join(location)
+        .map_err(|e| format!("Invalid redirect location '{}': {}", location, e))?;
+
+    let switch_to_get = status == reqwest::StatusCode::SEE_OTHER && previous.method() != reqwest::Method::HEAD
+        || matches!(status, reqwest::StatusCode::MOVED_PERMANENTLY | reqwest::StatusCode::FOUND)
+            && previous.method() == reqwest::Method::POST;
+
+    let mut next = if switch_to_get {
+        let mut next = request_head(previous);
+        *next.method_mut() = reqwest::Method::GET;
+        *next.url_mut() = url.clone();
+        for name in [
+            reqwest::header::CONTENT_TYPE,
+            reqwest::header::CONTENT_LENGTH,
+            reqwest::header::CONTENT_ENCODING,
+            reqwest::header::TRANSFER_ENCODING,
+        ] {
+            next.headers_mut().remove(name);
+        }
+        next
+    } else {
+        let mut next = replay.ok_or_else(|| "Cannot replay a streamed request body on redirect".to_string())?;
+        *next.url_mut() = url.clone();
+        next
+    };
+
+    // Do not leak credentials to another origin
+    if url.origin() != previous.url().origin() {
+        for name in [
+            reqwest::header::AUTHORIZATION,
+            reqwest::header::COOKIE,
+            reqwest::header::PROXY_AUTHORIZATION,
+            reqwest::header::WWW_AUTHENTICATE,
+        ] {
+            next.headers_mut().remove(name);
+        }
+    }
+    Ok(next)
+}
+
+// Error message including the underlying causes (TLS, proxy, timeout, ...)
+pub(crate) fn describe_request_error(error: &reqwest::Error) -> String {
+    let mut message = error.to_string();
+    let mut source = std::error::Error::source(error);
+    while let Some(cause) = source {
+        let cause_message = cause.to_string();
+        if !message.contains(&cause_message) {
+            message.push_str(": ");
+            message.push_str(&cause_message);
+        }
+        source = cause.source();
+    }
+    message
+}
+
+// Decoded view of a response body
+struct DecodedBody {
+    text: String,
+    content_type: Option<String>,
+    charset: Option<String>,
+    is_text: bool,
+}
+
+// Split a Content-Type header into the lowercase MIME essence and its charset parameter
+pub(crate) fn parse_content_type(header: &str) -> (String, Option<String>) {
+    let mut parts = header.split(';');
+    let mime = parts.next().unwrap_or("").trim().to_lowercase();
+    let charset = parts
+        .filter_map(|param| param.split_once('='))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("charset"))
+        .map(|(_, value)| value.trim().trim_matches('"').to_lowercase())
+        .filter(|value| !value.is_empty());
+    (mime, charset)
+}
+
+// Guess the MIME type from well-known magic bytes
+fn sniff_content_type(bytes: &[u8]) -> Option<&'static str> {
+    const SIGNATURES: &[(&[u8], &str)] = &[
+        (b"\x89PNG\r\n\x1a\n", "image/png"),
+        (b"\xff\xd8\xff", "image/jpeg"),
+        (b"GIF87a", "image/gif"),
+        (b"GIF89a", "image/gif"),
+        (b"BM", "image/bmp"),
+        (b"\x00\x00\x01\x00", "image/x-icon"),
+        (b"%PDF-", "application/pdf"),
+        (b"\x1f\x8b", "application/gzip"),
+        (b"PK\x03\x04", "application/zip"),
+        (b"7z\xbc\xaf\x27\x1c", "application/x-7z-compressed"),
+        (b"Rar!\x1a\x07", "application/vnd.rar"),
+        (b"BZh", "application/x-bzip2"),
+        (b"\xfd7zXZ\x00", "application/x-xz"),
+        (b"\x28\xb5\x2f\xfd", "application/zstd"),
+        (b"\x00asm", "application/wasm"),
+        (b"wOFF", "font/woff"),
+        (b"wOF2", "font/woff2"),
+        (b"OggS", "audio/ogg"),
+        (b"fLaC", "audio/flac"),
+        (b"ID3", "audio/mpeg"),
+        (b"\x1aE\xdf\xa3", "video/webm"),
+    ];
+
+    if bytes.len() >= 12 && &bytes[0..4] == b"RIFF" {
+        match &bytes[8..12] {
+            b"WEBP" => return Some("image/webp"),
+            b"WAVE" => return Some("audio/wav"),
+            b"AVI " => return Some("video/x-msvideo"),
+            _ => {}
+        }
+    }
+    if bytes.len() >= 12 && &bytes[4..8] == b"ftyp" {
+        return Some("video/mp4");
+    }
+
+    SIGNATURES
+        .iter()
+        .find(|(magic, _)| bytes.starts_with(magic))
+        .map(|(_, mime)| *mime)
+}
+
+// Whether a MIME type is textual; None when the type alone does not tell
+fn is_text_mime(mime: &str) -> Option<bool> {
+    if mime.starts_with("text/")
+        || mime.ends_with("+json")
+        || mime.ends_with("+xml")
+        || mime.ends_with("+yaml")
+    {
+        return Some(true);
+    }
+
+    match mime {
+        "application/json"
+        | "application/xml"
+        | "application/javascript"
+        | "application/ecmascript"
+        | "application/x-javascript"
+        | "application/x-www-form-urlencoded"
+        | "application/graphql"
+        | "application/yaml"
+        | "application/x-yaml"
+        | "application/toml"
+        | "application/sql"
+        | "application/x-ndjson"
+        | "application/csp-report"
+        | "image/svg+xml" => Some(true),
+        "" | "application/octet-stream" => None,
+        _ if mime.starts_with("image/")
+            || mime.starts_with("audio/")
+            || mime.starts_with("video/")
+            || mime.starts_with("font/")
+            || mime.starts_with("application/") => Some(false),
+        _ => None,
+    }
+}
+
+// Heuristic used when neither the header nor the magic bytes identify the body
+fn looks_like_text(bytes: &[u8]) -> bool {
+    let sample = &bytes[..bytes.len().min(8192)];
+    // A multi-byte sequence may be cut at the end of the sample
+    let valid = match std::str::from_utf8(sample) {
+        Ok(_) => true,
+        Err(e) => e.error_len().is_none() && sample.len() < bytes.len(),
+    };
+    valid && !sample
+        .iter()
+        .any(|&b| b == 0 || (b < 0x20 && !matches!(b, b'\t' | b'\n' | b'\r' | 0x0c | 0x1b)))
+}
+
+// Undo the Content-Encoding codings, last applied first. None when a coding is not supported.
+pub(crate) fn decode_content_encoding(encoding: &str, bytes: &[u8], limit: usize) -> Result<Option<Vec<u8>>, String> {
+    use std::io::Read;
+
+    // One byte past the limit is enough to tell the body is too large without inflating all of it
+    fn read_limited(reader: impl Read, limit: usize, decoded: &mut Vec<u8>) -> std::io::Result<usize> {
+        reader.take(limit as u64 + 1).read_to_end(decoded)
+    }
+
+    let codings: Vec<String> = encoding
+        .split(',')
+        .map(|coding| coding.trim().to_ascii_lowercase())
+        .filter(|coding| !coding.is_empty() && coding != "identity")
+        .collect();
+    let mut body = bytes.to_vec();
+    for coding in codings.iter().rev() {
+        let mut decoded = Vec::new();
+        let result = match coding.as_str() {
+            "gzip" | "x-gzip" => read_limited(flate2::read::MultiGzDecoder::new(body.as_slice()), limit, &mut decoded),
+            // deflate should be zlib-wrapped, but some servers send a raw deflate stream
+            "deflate" => read_limited(flate2::read::ZlibDecoder::new(body.as_slice()), limit, &mut decoded).or_else(|_| {
+                decoded.clear();
+                read_limited(flate2::read::DeflateDecoder::new(body.as_slice()), limit, &mut decoded)
+            }),
+            "br" => read_limited(brotli::Decompressor::new(body.as_slice(), 4096), limit, &mut decoded),
+            _ => return Ok(None),
+        };
+        result.map_err(|e| format!("Failed to decode {} response body: {}", coding, e))?;
+        if decoded.len() > limit {
+            return Err(format!("Decoded {} response body is larger than {} bytes", coding, limit));
+        }
+        body = decoded;
+    }
+    Ok(Some(body))
+}
+
+// Upper bound for a decompressed response body
+const DECODED_BODY_LIMIT: usize = 256 * 1024 * 1024;
+
+// Detect content type and charset, and decode the body to text when it is textual
+fn decode_response_body(content_type_header: Option<&str>, bytes: &[u8]) -> DecodedBody {
+    let (header_mime, header_charset) = content_type_header
+        .map(parse_content_type)
+        .unwrap_or_default();
+
+    let sniffed = sniff_content_type(bytes);
+    let content_type = if header_mime.is_empty() || header_mime == "application/octet-stream" {
+        sniffed.map(str::to_string).or_else(|| Some(header_mime.clone()).filter(|m| !m.is_empty()))
+    } else {
+        Some(header_mime.clone())
+    };
+
+    let is_text = bytes.is_empty()
+        || match content_type.as_deref().and_then(is_text_mime) {
+            Some(is_text) => is_text,
+            None => sniffed.is_none() && looks_like_text(bytes),
+        };
+
+    if !is_text {
+        return DecodedBody {
+            text: String::new(),
+            content_type,
+            charset: None,
+            is_text,
+        };
+    }
+
+    // A BOM wins over the declared charset, as browsers do
+    let (encoding, bom_len) = match encoding_rs::Encoding::for_bom(bytes) {
+        Some((encoding, bom_len)) => (encoding, bom_len),
+        None => (
+            header_charset
+                .as_deref()
+                .and_then(|label| encoding_rs::Encoding::for_label(label.as_bytes()))
+                .unwrap_or(encoding_rs::UTF_8),
+            0,
+        ),
+    };
+    let (text, _) = encoding.decode_without_bom_handling(&bytes[bom_len..]);
+
+    DecodedBody {
+        text: text.into_owned(),
+        content_type,
+        charset: Some(encoding.name().to_lowercase()),
+        is_text,
+    }
+}
+
+#[tauri::command]
+pub async fn http_request(
+    app: tauri::AppHandle,
+    sessions: tauri::State<'_, HttpSessions>,
+    history: tauri::State<'_, HttpHistory>,
+    secrets: tauri::State<'_, SecretStore>,
+    request: HttpRequest,
+    assertions: Option<Vec<ResponseAssertion>>,
+    environment: Option<HistoryEnvironment>,
+) -> Result<HttpResponse, String> {
+    let options = request.options.clone().unwrap_or_default();
+    let session = request.session.as_deref().unwrap_or(DEFAULT_HTTP_SESSION);
+    let client = sessions.client(session, &options)?;
+    // History keeps the {{secret:...}} references, never the values
+    let mask = secrets.mask(&request);
+    let mut response = execute_http_request(&client, secrets.resolve(request.clone())?)
+        .await
+        .map_err(|e| mask.apply(&e))?;
+    mask.apply_response(&mut response);
+    if let Some(assertions) = assertions {
+        response.assertions = evaluate_assertions(&assertions, &response);
+    }
+    // 历史记录写入失败不影响请求结果，但会在响应中报告
+    response.history_error = history.record(&app, request, response.clone(), environment).await.err();
+    Ok(response)
+}
+
+// Build one multipart part: text, base64 file content, or a file streamed from disk
+fn build_multipart_part(entry: MultipartEntry) -> Result<reqwest::multipart::Part, String> {
+    let mut part = if entry.r#type == "file" {
+        let path = entry.path.as_deref().filter(|path| !path.is_empty());
+        let filename = entry.filename.clone().or_else(|| {
+            path.and_then(|path| std::path::Path::new(path).file_name())
+                .map(|name| name.to_string_lossy().into_owned())
+        });
+        let content_type = entry.content_type.clone().filter(|ct| !ct.is_empty()).unwrap_or_else(|| {
+            filename
+                .as_deref()
+                .map(|name| mime_guess::from_path(name).first_or_octet_stream().to_string())
+                .unwrap_or_else(|| "application/octet-stream".to_string())
+        });
+
+        let part = match path {
+            Some(path) => {
+                // 从磁盘流式读取，避免把大文件整体读入内存
+                let file = std::fs::File::open(path).map_err(|e| format!("Failed to open {}: {}", path, e))?;
+                let length = file
+                    .metadata()
+                    .map_err(|e| format!("Failed to read {}: {}", path, e))?
+                    .len();
+                reqwest::multipart::Part::stream_with_length(tokio::fs::File::from_std(file), length)
+            }
+            None => {
+                let bytes = general_purpose::STANDARD
+                    .decode(&entry.value)
+                    .map_err(|e| format!("Invalid base64 content for multipart field '{}': {}", entry.key, e))?;
+                reqwest::multipart::Part::bytes(bytes)
+            }
+        };
+        let part = part
+            .mime_str(&content_type)
+            .map_err(|_| format!("Invalid content type for multipart field '{}': {}", entry.key, content_type))?;
+        match filename {
+            Some(filename) => part.file_name(filename),
+            None => part,
+        }
+    } else {
+        let part = reqwest::multipart::Part::text(entry.value.clone());
+        match entry.content_type.as_deref().filter(|ct| !ct.is_empty()) {
+            Some(content_type) => part
+                .mime_str(content_type)
+                .map_err(|_| format!("Invalid content type for multipart field '{}': {}", entry.key, content_type))?,
+            None => part,
+        }
+    };
+
+    if let Some(headers) = entry.headers.filter(|headers| !headers.is_empty()) {
+        let mut header_map = reqwest::header::HeaderMap::new();
+        for (name, value) in headers {
+            let name = reqwest::header::HeaderName::from_bytes(name.as_bytes())
+                .map_err(|_| format!("Invalid header name in multipart field '{}': {}", entry.key, name))?;
+            let value = reqwest::header::HeaderValue::from_str(&value)
+                .map_err(|_| format!("Invalid header value in multipart field '{}': {}", entry.key, value))?;
+            header_map.insert(name, value);
+        }
+        part = part.headers(header_map);
+    }
+    Ok(part)
+}
+
+// Serialize a typed body onto the request; Content-Type is only set when the caller did not set one
+fn apply_request_body(
+    req_builder: reqwest::RequestBuilder,
+    method: &reqwest::Method,
+    body: RequestBody,
+    has_content_type: bool,
+) -> Result<reqwest::RequestBuilder, String> {
+    let with_content_type = |req_builder: reqwest::RequestBuilder, content_type: &str| {
+        if has_content_type {
+            req_builder
+        } else {
+            req_builder.header(reqwest::header::CONTENT_TYPE, content_type)
+        }
+    };
+
+    let req_builder = match body {
+        RequestBody::Text { content, content_type } => {
+            let content_type = content_type.filter(|ct| !ct.is_empty()).unwrap_or_else(|| "text/plain; charset=utf-8".to_string());
+            with_content_type(req_builder, &content_type).body(content)
+        }
+        RequestBody::Json { content } => with_content_type(req_builder, "application/json").body(content),
+        RequestBody::Urlencoded { fields } => {
+            // reqwest keeps a Content-Type the caller already set
+            let pairs: Vec<(&str, &str)> = fields.iter().map(|field| (field.key.as_str(), field.value.as_str())).collect();
+            req_builder.form(&pairs)
+        }
+        RequestBody::Binary { path, base64, content_type } => {
+            let path = path.filter(|path| !path.is_empty());
+            let content_type = content_type.filter(|ct| !ct.is_empty()).unwrap_or_else(|| {
+                path.as_deref()
+                    .map(|path| mime_guess::from_path(path).first_or_octet_stream().to_string())
+                    .unwrap_or_else(|| "application/octet-stream".to_string())
+            });
+            let req_builder = with_content_type(req_builder, &content_type);
+            match (path, base64) {
+                (Some(path), _) => {
+                    let file = std::fs::File::open(&path).map_err(|e| format!("Failed to open {}: {}", path, e))?;
+                    let length = file
+                        .metadata()
+                        .map_err(|e| format!("Failed to read {}: {}", path, e))?
+                        .len();
+                    req_builder
+                        .header(reqwest::header::CONTENT_LENGTH, length)
+                        .body(tokio::fs::File::from_std(file))
+                }
+                (None, Some(base64)) => {
+                    let bytes = general_purpose::STANDARD
+                        .decode(base64.trim())
+                        .map_err(|e| format!("Invalid base64 body: {}", e))?;
+                    req_builder.body(bytes)
+                }
+                (None, None) => req_builder,
+            }
+        }
+        RequestBody::Multipart { entries } => {
+            // 构建 multipart form
+            let mut form = reqwest::multipart::Form::new();
+            for entry in entries {
+                let key = entry.key.clone();
+                form = form.part(key, build_multipart_part(entry)?);
+            }
+            req_builder.multipart(form)
+        }
+        RequestBody::Graphql { query, variables, operation_name } => {
+            if method == reqwest::Method::GET {
+                // GraphQL over GET puts the operation in the query string
+                req_builder.query(&graphql_query_params(query, variables, operation_name))
+            } else {
+                let payload = graphql_payload(query, variables, operation_name);
+                with_content_type(req_builder, "application/json").body(payload.to_string())
+            }
+        }
+    };
+    Ok(req_builder)
+}
+
+pub(crate) fn graphql_query_params(
+    query: String,
+    variables: Option<serde_json::Value>,
+    operation_name: Option<String>,
+) -> Vec<(&'static str, String)> {
+    let mut params = vec![("query", query)];
+    if let Some(variables) = variables.filter(|v| !v.is_null()) {
+        params.push(("variables", variables.to_string()));
+    }
+    if let Some(operation_name) = operation_name.filter(|name| !name.is_empty()) {
+        params.push(("operationName", operation_name));
+    }
+    params
+}
+
+pub(crate) fn graphql_payload(
+    query: String,
+    variables: Option<serde_json::Value>,
+    operation_name: Option<String>,
+) -> serde_json::Value {
+    let mut payload = serde_json::json!({ "query": query });
+    if let Some(variables) = variables.filter(|v| !v.is_null()) {
+        payload["variables"] = variables;
+    }
+    if let Some(operation_name) = operation_name.filter(|name| !name.is_empty()) {
+        payload["operationName"] = serde_json::Value::String(operation_name);
+    }
+    payload
+}
+
+pub(crate) fn parse_http_method(method: &str) -> Result<reqwest::Method, String> {
+    match method {
+        "GET" => Ok(reqwest::Method::GET),
+        "POST" => Ok(reqwest::Method::POST),
+        "PUT" => Ok(reqwest::Method::PUT),
+        "DELETE" => Ok(reqwest::Method::DELETE),
+        "PATCH" => Ok(reqwest::Method::PATCH),
+        "HEAD" => Ok(reqwest::Method::HEAD),
+        "OPTIONS" => Ok(reqwest::Method::OPTIONS),
+        _ => Err(format!("Unsupported HTTP method: {}", method)),
+    }
+}
+
+// Turn an HttpRequest into a reqwest request builder: method, headers and body
+pub(crate) fn build_http_request(client: &reqwest::Client, request: HttpRequest) -> Result<reqwest::RequestBuilder, String> {
+    let method = parse_http_method(&request.method)?;
+    let mut req_builder = client.request(method.clone(), &request.url);
+
+    let has_content_type = request.headers.keys().any(|key| key.eq_ignore_ascii_case("content-type"));
+    let is_multipart = matches!(request.body, Some(RequestBody::Multipart { .. }));
+
+    // 添加请求头
+    for (key, value) in request.headers {
+        // 如果是 multipart 请求，Content-Type（含 boundary）由 reqwest 自动设置
+        if !(is_multipart && key.eq_ignore_ascii_case("content-type")) {
+            req_builder = req_builder.header(&key, &value);
+        }
+    }
+
+    // 添加请求体
+    if let Some(body) = request.body {
+        req_builder = apply_request_body(req_builder, &method, body, has_content_type)?;
+    }
+
+    Ok(req_builder)
+}
+
+// Characters left as-is by RFC 3986 percent-encoding, as required by SigV4 and OAuth 1.0a
+const RFC3986_ESCAPE: &percent_encoding::AsciiSet = &percent_encoding::NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'_')
+    .remove(b'.')
+    .remove(b'~');
+
+pub(crate) fn rfc3986_encode(value: &str) -> String {
+    percent_encoding::utf8_percent_encode(value, RFC3986_ESCAPE).to_string()
+}
+
+fn auth_header_value(value: &str) -> Result<reqwest::header::HeaderValue, String> {
+    reqwest::header::HeaderValue::from_str(value).map_err(|e| format!("Invalid authentication header value: {}", e))
+}
+
+// host[:port] as sent in the Host header
+pub(crate) fn url_authority(url: &reqwest::Url) -> String {
+    let host = url.host_str().unwrap_or_default();
+    match url.port() {
+        Some(port) => format!("{}:{}", host, port),
+        None => host.to_string(),
+    }
+}
+
+// Body bytes when the body is held in memory (streamed file bodies are not)
+fn request_body_bytes(request: &reqwest::Request) -> Option<&[u8]> {
+    request.body().and_then(|body| body.as_bytes())
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Result<Vec<u8>, String> {
+    use hmac::{Hmac, Mac};
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).map_err(|e| format!("Failed to create HMAC: {}", e))?;
+    mac.update(data);
+    Ok(mac.finalize().into_bytes().to_vec())
+}
+
+// ("YYYYMMDD", "YYYYMMDDTHHMMSSZ") for a unix timestamp in UTC
+fn amz_dates(unix_secs: u64) -> (String, String) {
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let days = (unix_secs / 86400) as i64 + 719468;
+    let era = days.div_euclid(146097);
+    let doe = days.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    let secs = unix_secs % 86400;
+    let date = format!("{:04}{:02}{:02}", year, month, day);
+    let timestamp = format!("{}T{:02}{:02}{:02}Z", date, secs / 3600, secs % 3600 / 60, secs % 60);
+    (date, timestamp)
+}
+
+pub(crate) fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+pub(crate) fn unix_now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+// AWS Signature Version 4 Authorization header for a request that already carries
+// its x-amz-date (and other x-amz-*) headers
+fn aws_sigv4_authorization(
+    request: &reqwest::Request,
+    payload_hash: &str,
+    access_key_id: &str,
+    secret_access_key: &str,
+    region: &str,
+    service: &str,
+) -> Result<String, String> {
+    let (url, headers) = (request.url(), request.headers());
+    let amz_date = headers
+        .get("x-amz-date")
+        .and_then(|value| value.to_str().ok())
+        .ok_or_else(|| "Missing x-amz-date header".to_string())?;
+    let date = amz_date.get(..8).ok_or_else(|| format!("Invalid x-amz-date: {}", amz_date))?;
+
+    // S3 signs the path as sent, other services encode each segment once more
+    let canonical_uri = if url.path().is_empty() {
+        "/".to_string()
+    } else if service == "s3" {
+        url.path().to_string()
+    } else {
+        url.path().split('/').map(rfc3986_encode).collect::<Vec<_>>().join("/")
+    };
+
+    let mut query: Vec<(String, String)> = url
+        .query_pairs()
+        .map(|(key, value)| (rfc3986_encode(&key), rfc3986_encode(&value)))
+        .collect();
+    query.sort();
+    let canonical_query = query
+        .iter()
+        .map(|(key, value)| format!("{}={}", key, value))
+        .collect::<Vec<_>>()
+        .join("&");
+
+    let mut canonical_headers: std::collections::BTreeMap<String, Vec<String>> = std::collections::BTreeMap::new();
+    canonical_headers.insert("host".to_string(), vec![url_authority(url)]);
+    for (name, value) in headers {
+        if matches!(name.as_str(), "authorization" | "user-agent" | "expect" | "x-amzn-trace-id") {
+            continue;
+        }
+        let value = String::from_utf8_lossy(value.as_bytes());
+        canonical_headers
+            .entry(name.as_str().to_string())
+            .or_default()
+            .push(value.split_whitespace().collect::<Vec<_>>().join(" "));
+    }
+    let signed_headers = canonical_headers.keys().cloned().collect::<Vec<_>>().join(";");
+    let header_block: String = canonical_headers
+        .iter()
+        .map(|(name, values)| format!("{}:{}\n", name, values.join(",")))
+        .collect();
+
+    let canonical_request = format!(
+        "{}\n{}\n{}\n{}\n{}\n{}",
+        request.method(), canonical_uri, canonical_query, header_block, signed_headers, payload_hash
+    );
+    let scope = format!("{}/{}/{}/aws4_request", date, region, service);
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        amz_date,
+        scope,
+        hex::encode(Sha256::digest(canonical_request.as_bytes()))
+    );
+
+    let mut key = hmac_sha256(format!("AWS4{}", secret_access_key).as_bytes(), date.as_bytes())?;
+    for part in [region, service, "aws4_request"] {
+        key = hmac_sha256(&key, part.as_bytes())?;
+    }
+    let signature = hex::encode(hmac_sha256(&key, string_to_sign.as_bytes())?);
+
+    Ok(format!(
+        "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+        access_key_id, scope, signed_headers, signature
+    ))
+}
+
+// OAuth 1.0a Authorization header signed with HMAC-SHA1. Query parameters and a
+// form-urlencoded body are part of the signature base string.
+fn oauth1_authorization(
+    method: &str,
+    url: &reqwest::Url,
+    form_body: Option<&[u8]>,
+    oauth_params: &[(&str, &str)],
+    consumer_secret: &str,
+    token_secret: &str,
+    realm: Option<&str>,
+) -> Result<String, String> {
+    use hmac::{Hmac, Mac};
+
+    let mut params: Vec<(String, String)> = oauth_params
+        .iter()
+        .map(|(key, value)| (rfc3986_encode(key), rfc3986_encode(value)))
+        .collect();
+    params.extend(url.query_pairs().map(|(key, value)| (rfc3986_encode(&key), rfc3986_encode(&value))));
+    if let Some(body) = form_body {
+        for pair in String::from_utf8_lossy(body).split('&').filter(|pair| !pair.is_empty()) {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            let decode = |part: &str| {
+                percent_encoding::percent_decode_str(&part.replace('+', " "))
+                    .decode_utf8_lossy()
+                    .into_owned()
+            };
+            params.push((rfc3986_encode(&decode(key)), rfc3986_encode(&decode(value))));
+        }
+    }
+    params.sort();
+    let normalized = params
+        .iter()
+        .map(|(key, value)| format!("{}={}", key, value))
+        .collect::<Vec<_>>()
+        .join("&");
+
+    let base_url = format!("{}://{}{}", url.scheme(), url_authority(url).to_lowercase(), url.path());
+    let base_string = format!(
+        "{}&{}&{}",
+        method.to_uppercase(),
+        rfc3986_encode(&base_url),
+        rfc3986_encode(&normalized)
+    );
+    let signing_key = format!("{}&{}", rfc3986_encode(consumer_secret), rfc3986_encode(token_secret));
+    let mut mac = Hmac::<sha1::Sha1>::new_from_slice(signing_key.as_bytes())
+        .map_err(|e| format!("Failed to create HMAC: {}", e))?;
+    mac.update(base_string.as_bytes());
+    let signature = general_purpose::STANDARD.encode(mac.finalize().into_bytes());
+
+    let mut fields = Vec::new();
+    if let Some(realm) = realm {
+        fields.push(format!("realm=\"{}\"", rfc3986_encode(realm)));
+    }
+    for (key, value) in oauth_params.iter().copied().chain([("oauth_signature", signature.as_str())]) {
+        fields.push(format!("{}=\"{}\"", rfc3986_encode(key), rfc3986_encode(value)));
+    }
+    Ok(format!("OAuth {}", fields.join(", ")))
+}
+
+// Parameters of a "name=value, name=\"quoted value\"" list, stopping at the next scheme
+fn parse_auth_params(input: &str) -> HashMap<String, String> {
+    let mut params = HashMap::new();
+    let mut rest = input;
+    loop {
+        rest = rest.trim_start_matches(|c: char| c == ',' || c.is_whitespace());
+        let Some(eq) = rest.find('=') else { break };
+        let name = rest[..eq].trim();
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            break;
+        }
+        rest = rest[eq + 1..].trim_start();
+        let value = if let Some(quoted) = rest.strip_prefix('"') {
+            let mut value = String::new();
+            let mut end = quoted.len();
+            let mut chars = quoted.char_indices();
+            while let Some((i, c)) = chars.next() {
+                match c {
+                    '\\' => {
+                        if let Some((_, escaped)) = chars.next() {
+                            value.push(escaped);
+                        }
+                    }
+                    '"' => {
+                        end = i + 1;
+                        break;
+                    }
+                    _ => value.push(c),
+                }
+            }
+            rest = &quoted[end..];
+            value
+        } else {
+            let end = rest.find(',').unwrap_or(rest.len());
+            let value = rest[..end].trim().to_string();
+            rest = &rest[end..];
+            value
+        };
+        params.insert(name.to_ascii_lowercase(), value);
+    }
+    params
+}
+
+// Digest challenge from the WWW-Authenticate headers of a 401 response
+fn digest_challenge(headers: &reqwest::header::HeaderMap) -> Option<HashMap<String, String>> {
+    headers
+        .get_all(reqwest::header::WWW_AUTHENTICATE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .find_map(|value| {
+            let lower = value.to_ascii_lowercase();
+            let start = lower.match_indices("digest").map(|(i, _)| i).find(|&i| {
+                (i == 0 || lower[..i].ends_with([',', ' ']))
+                    && lower[i + 6..].starts_with(char::is_whitespace)
+            })?;
+            let params = parse_auth_params(&value[start + 6..]);
+            params.contains_key("nonce").then_some(params)
+        })
+}
+
+fn quote_auth_param(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+// Digest Authorization header (RFC 7616) answering a server challenge
+fn digest_authorization(
+    method: &str,
+    url: &reqwest::Url,
+    body: Option<&[u8]>,
+    username: &str,
+    password: &str,
+    challenge: &HashMap<String, String>,
+    cnonce: &str,
+) -> Result<String, String> {
+    let realm = challenge.get("realm").map(String::as_str).unwrap_or_default();
+    let nonce = challenge
+        .get("nonce")
+        .ok_or_else(|| "Digest challenge has no nonce".to_string())?;
+    let algorithm = challenge
+        .get("algorithm")
+        .map(|algorithm| algorithm.to_ascii_uppercase())
+        .unwrap_or_else(|| "MD5".to_string());
+    let (base_algorithm, session) = match algorithm.strip_suffix("-SESS") {
+        Some(base) => (base, true),
+        None => (algorithm.as_str(), false),
+    };
+    let hash: fn(&[u8]) -> String = match base_algorithm {
+        "MD5" => |data| hex::encode(md5::Md5::digest(data)),
+        "SHA-256" => |data| hex::encode(Sha256::digest(data)),
+        other => return Err(format!("Unsupported Digest algorithm: {}", other)),
+    };
+
+    let offered: Vec<String> = challenge
+        .get("qop")
+        .map(|qop| qop.split(',').map(|option| option.trim().to_ascii_lowercase()).collect())
+        .unwrap_or_default();
+    let qop = ["auth", "auth-int"].into_iter().find(|qop| offered.iter().any(|option| option == qop));
+
+    let uri = match url.query() {
+        Some(query) => format!("{}?{}", url.path(), query),
+        None => url.path().to_string(),
+    };
+    let nc = "00000001";
+
+    let mut ha1 = hash(format!("{}:{}:{}", username, realm, password).as_bytes());
+    if session {
+        ha1 = hash(format!("{}:{}:{}", ha1, nonce, cnonce).as_bytes());
+    }
+    let ha2 = if qop == Some("auth-int") {
+        hash(format!("{}:{}:{}", method, uri, hash(body.unwrap_or_default())).as_bytes())
+    } else {
+        hash(format!("{}:{}", method, uri).as_bytes())
+    };
+    let response = match qop {
+        Some(qop) => hash(format!("{}:{}:{}:{}:{}:{}", ha1, nonce, nc, cnonce, qop, ha2).as_bytes()),
+        None => hash(format!("{}:{}:{}", ha1, nonce, ha2).as_bytes()),
+    };
+
+    let mut header = format!(
+        "Digest username={}, realm={}, nonce={}, uri={}, algorithm={}, response=\"{}\"",
+        quote_auth_param(username),
+        quote_auth_param(realm),
+        quote_auth_param(nonce),
+        quote_auth_param(&uri),
+        algorithm,
+        response
+    );
+    if let Some(opaque) = challenge.get("opaque") {
+        header.push_str(&format!(", opaque={}", quote_auth_param(opaque)));
+    }
+    if let Some(qop) = qop {
+        header.push_str(&format!(", qop={}, nc={}, cnonce=\"{}\"", qop, nc, cnonce));
+    }
+    Ok(header)
+}
+
+// Add the credentials of an auth scheme to a built request. Digest is answered
+// later, once the server has sent its challenge.
+pub(crate) fn apply_request_auth(request: &mut reqwest::Request, auth: &RequestAuth) -> Result<(), String> {
+    match auth {
+        RequestAuth::Basic { username, password } => {
+            let credentials = format!("{}:{}", username, password.as_deref().unwrap_or_default());
+            let value = format!("Basic {}", general_purpose::STANDARD.encode(credentials));
+            request.headers_mut().insert(reqwest::header::AUTHORIZATION, auth_header_value(&value)?);
+        }
+        RequestAuth::Bearer { token } => {
+            let value = format!("Bearer {}", token.trim());
+            request.headers_mut().insert(reqwest::header::AUTHORIZATION, auth_header_value(&value)?);
+        }
+        RequestAuth::ApiKey { key, value, location } => match location {
+            ApiKeyLocation::Header => {
+                let name = reqwest::header::HeaderName::from_bytes(key.trim().as_bytes())
+                    .map_err(|e| format!("Invalid API key header name '{}': {}", key, e))?;
+                request.headers_mut().insert(name, auth_header_value(value)?);
+            }
+            ApiKeyLocation::Query => {
+                request.url_mut().query_pairs_mut().append_pair(key, value);
+            }
+        },
+        RequestAuth::Digest { .. } => {}
+        RequestAuth::AwsSigv4 {
+            access_key_id,
+            secret_access_key,
+            session_token,
+            region,
+            service,
+        } => {
+            let payload_hash = match request.body() {
+                None => hex::encode(Sha256::digest(b"")),
+                Some(body) => match body.as_bytes() {
+                    Some(bytes) => hex::encode(Sha256::digest(bytes)),
+                    None => "UNSIGNED-PAYLOAD".to_string(),
+                },
+            };
+            let (_, amz_date) = amz_dates(unix_now());
+            let headers = request.headers_mut();
+            headers.remove(reqwest::header::AUTHORIZATION);
+            headers.insert("x-amz-date", auth_header_value(&amz_date)?);
+            headers.insert("x-amz-content-sha256", auth_header_value(&payload_hash)?);
+            if let Some(token) = session_token.as_deref().filter(|token| !token.is_empty()) {
+                headers.insert("x-amz-security-token", auth_header_value(token)?);
+            }
+
+            let authorization = aws_sigv4_authorization(
+                request,
+                &payload_hash,
+                access_key_id,
+                secret_access_key,
+                region,
+                service,
+            )?;
+            request
+                .headers_mut()
+                .insert(reqwest::header::AUTHORIZATION, auth_header_value(&authorization)?);
+        }
+        RequestAuth::Oauth1 {
+            consumer_key,
+            consumer_secret,
+            token,
+            token_secret,
+            realm,
+            callback,
+            verifier,
+        } => {
+            use rand::Rng;
+
+            let nonce: String = rand::thread_rng()
+                .sample_iter(&rand::distributions::Alphanumeric)
+                .take(32)
+                .map(char::from)
+                .collect();
+            let timestamp = unix_now().to_string();
+            let mut oauth_params = vec![("oauth_consumer_key", consumer_key.as_str())];
+            if let Some(callback) = callback.as_deref() {
+                oauth_params.push(("oauth_callback", callback));
+            }
+            oauth_params.push(("oauth_nonce", &nonce));
+            oauth_params.push(("oauth_signature_method", "HMAC-SHA1"));
+            oauth_params.push(("oauth_timestamp", &timestamp));
+            if let Some(token) = token.as_deref().filter(|token| !token.is_empty()) {
+                oauth_params.push(("oauth_token", token));
+            }
+            if let Some(verifier) = verifier.as_deref() {
+                oauth_params.push(("oauth_verifier", verifier));
+            }
+            oauth_params.push(("oauth_version", "1.0"));
+
+            let is_form = request
+                .headers()
+                .get(reqwest::header::CONTENT_TYPE)
+                .and_then(|value| value.to_str().ok())
+                .is_some_and(|value| parse_content_type(value).0 == "application/x-www-form-urlencoded");
+            let authorization = oauth1_authorization(
+                request.method().as_str(),
+                request.url(),
+                request_body_bytes(request).filter(|_| is_form),
+                &oauth_params,
+                consumer_secret,
+                token_secret.as_deref().unwrap_or_default(),
+                realm.as_deref(),
+            )?;
+            request
+                .headers_mut()
+                .insert(reqwest::header::AUTHORIZATION, auth_header_value(&authorization)?);
+        }
+    }
+    Ok(())
+}
+
+// Why sending failed: the request could not be authenticated, or the transport failed
+pub(crate) enum SendError {
+    Auth(String),
+    Request(reqwest::Error),
+}
+
+impl SendError {
+    fn describe(&self) -> String {
+        match self {
+            SendError::Auth(message) => message.clone(),
+            SendError::Request(error) => describe_request_error(error),
+        }
+    }
+
+    // Short category used to group failures, e.g. in benchmark reports
+    pub(crate) fn kind(&self) -> &'static str {
+        match self {
+            SendError::Auth(_) => "auth",
+            SendError::Request(error) if error.is_timeout() => "timeout",
+            SendError::Request(error) if error.is_connect() => "connect",
+            SendError::Request(error) if error.is_redirect() => "redirect",
+            SendError::Request(error) if error.is_body() => "body",
+            SendError::Request(error) if error.is_decode() => "decode",
+            SendError::Request(_) => "request",
+        }
+    }
+}
+
+// Send a request with its authentication applied. A Digest challenge is answered
+// with a second request when the body can be replayed.
+pub(crate) async fn send_http_request(
+    client: &reqwest::Client,
+    request: reqwest::Request,
+    auth: Option<&RequestAuth>,
+) -> Result<reqwest::Response, String> {
+    dispatch_http_request(client, request, auth).await.map_err(|e| e.describe())
+}
+
+pub(crate) async fn dispatch_http_request(
+    client: &reqwest::Client,
+    mut request: reqwest::Request,
+    auth: Option<&RequestAuth>,
+) -> Result<reqwest::Response, SendError> {
+    if let Some(auth) = auth {
+        apply_request_auth(&mut request, auth).map_err(SendError::Auth)?;
+    }
+    let replay = match auth {
+        Some(RequestAuth::Digest { .. }) => request.try_clone(),
+        _ => None,
+    };
+    let response = client.execute(request).await.map_err(SendError::Request)?;
+
+    if let (Some(mut replay), Some(RequestAuth::Digest { username, password })) = (replay, auth) {
+        if response.status() == reqwest::StatusCode::UNAUTHORIZED {
+            if let Some(challenge) = digest_challenge(response.headers()) {
+                let cnonce = hex::encode(rand::random::<[u8; 16]>());
+                let authorization = digest_authorization(
+                    replay.method().as_str(),
+                    replay.url(),
+                    request_body_bytes(&replay),
+                    username,
+                    password,
+                    &challenge,
+                    &cnonce,
+                )
+                .map_err(SendError::Auth)?;
+                let value = auth_header_value(&authorization).map_err(SendError::Auth)?;
+                replay.headers_mut().insert(reqwest::header::AUTHORIZATION, value);
+                return client.execute(replay).await.map_err(SendError::Request);
+            }
+        }
+    }
+    Ok(response)
+}
+
+// Send an HttpRequest with the given client
+pub(crate) async fn execute_http_request(client: &reqwest::Client, request: HttpRequest) -> Result<HttpResponse, String> {
+    let options = request.options.clone().unwrap_or_default();
+    let auth = request.auth.clone();
+    let req_builder = build_http_request(client, request)?;
+
+    let mut current = match req_builder.build() {
+        Ok(built) => built,
+        Err(e) => return Ok(HttpResponse::from_error(describe_request_error(&e))),
+    };
+    // Compressed responses are decoded unless turned off, but only asked for when turned on
+    let decompress = options.decompress != Some(false);
+    if options.decompress == Some(true) && !current.headers().contains_key(reqwest::header::ACCEPT_ENCODING) {
+        current.headers_mut().insert(
+            reqwest::header::ACCEPT_ENCODING,
+            reqwest::header::HeaderValue::from_static("gzip, deflate, br"),
+        );
+    }
+    // Credentials are only applied to hops on the origin of the original request
+    let auth_origin = current.url().origin();
+    let capture_redirects = options.capture_redirects.unwrap_or(false);
+    let max_redirects = options.max_redirects.unwrap_or(10);
+    let mut redirects = Vec::new();
+
+    loop {
+        let previous = capture_redirects.then(|| request_head(&current));
+        let replay = if capture_redirects { current.try_clone() } else { None };
+
+        let hop_auth = auth.as_ref().filter(|_| current.url().origin() == auth_origin);
+
+        let trace = std::sync::Arc::new(Mutex::new(ConnectionTrace::default()));
+        let started = std::time::Instant::now();
+
+        let response = match HTTP_TRACE.scope(trace.clone(), send_http_request(client, current, hop_auth)).await {
+            Ok(response) => response,
+            Err(e) => {
+                let mut failed = HttpResponse::from_error(e);
+                failed.redirects = redirects;
+                return Ok(failed);
+            }
+        };
+        let headers_at = std::time::Instant::now();
+        let status = response.status().as_u16();
+
+        // 手动跟随重定向，记录每一跳; with max_redirects 0 the 3xx itself is the response
+        if let Some(previous) = previous.filter(|_| max_redirects > 0 && response.status().is_redirection()) {
+            if let Some(location) = response
+                .headers()
+                .get(reqwest::header::LOCATION)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string)
+            {
+                redirects.push(RedirectHop {
+                    url: response.url().to_string(),
+                    status,
+                    headers: header_map_to_hash_map(response.headers()),
+                    location: location.clone(),
+                    elapsed_ms: ms_between(started, headers_at),
+                });
+
+                if redirects.len() > max_redirects {
+                    let mut failed = HttpResponse::from_error(format!("Too many redirects (limit {})", max_redirects));
+                    failed.redirects = redirects;
+                    return Ok(failed);
+                }
+
+                match next_redirect_request(&previous, replay, response.status(), &location) {
+                    Ok(next) => {
+                        current = next;
+                        continue;
+                    }
+                    Err(e) => {
+                        let mut failed = HttpResponse::from_error(e);
+                        failed.redirects = redirects;
+                        return Ok(failed);
+                    }
+                }
+            }
+        }
+
+        let url = response.url().to_string();
+        let version = response.version();
+        let connection = response
+            .extensions()
+            .get::<hyper_util::client::legacy::connect::HttpInfo>()
+            .map(|info| (info.local_addr().to_string(), info.remote_addr().to_string()));
+
+        // 获取响应头
+        let headers = header_map_to_hash_map(response.headers());
+
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        let content_encoding = response
+            .headers()
+            .get(reqwest::header::CONTENT_ENCODING)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+
+        // 获取原始响应体字节，按内容类型决定是否解码为文本
+        let bytes = match response.bytes().await {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                let mut failed = HttpResponse::from_error(format!("Error reading response body: {}", describe_request_error(&e)));
+                failed.status = status;
+                failed.headers = headers;
+                failed.url = url;
+                failed.redirects = redirects;
+                return Ok(failed);
+            }
+        };
+        let finished = std::time::Instant::now();
+
+        // 按 Content-Encoding 解压，保留传输时的原始字节
+        let wire_size = bytes.len();
+        let encoded = content_encoding.as_deref().is_some_and(|encoding| {
+            encoding
+                .split(',')
+                .any(|coding| !coding.trim().is_empty() && !coding.trim().eq_ignore_ascii_case("identity"))
+        });
+        let mut decode_error = None;
+        let mut wire_body_base64 = None;
+        let bytes = match content_encoding.as_deref().filter(|_| decompress && encoded) {
+            Some(encoding) => match decode_content_encoding(encoding, &bytes, DECODED_BODY_LIMIT) {
+                Ok(Some(decompressed)) => {
+                    wire_body_base64 = Some(general_purpose::STANDARD.encode(&bytes));
+                    std::borrow::Cow::Owned(decompressed)
+                }
+                Ok(None) => std::borrow::Cow::Borrowed(&bytes[..]),
+                Err(e) => {
+                    decode_error = Some(e);
+                    std::borrow::Cow::Borrowed(&bytes[..])
+                }
+            },
+            None => std::borrow::Cow::Borrowed(&bytes[..]),
+        };
+        let mut decoded = decode_response_body(content_type.as_deref(), &bytes);
+        if encoded && wire_body_base64.is_none() {
+            // 仍是压缩数据，不能按文本显示
+            decoded.text = String::new();
+            decoded.is_text = false;
+        }
+        let timing = trace.lock().ok().map(|trace| trace.timing(started, headers_at, finished));
+        let (local_addr, remote_addr) = connection.unzip();
+        // A reused connection keeps the protocol it negotiated, which the version reflects
+        let negotiated_h2 = trace.lock().ok().and_then(|trace| trace.negotiated_h2);
+        let alpn_protocol = url.starts_with("https:").then(|| {
+            match negotiated_h2.unwrap_or(version == reqwest::Version::HTTP_2) {
+                true => Some("h2".to_string()),
+                false if version < reqwest::Version::HTTP_2 => Some("http/1.1".to_string()),
+                false => None,
+            }
+        });
+
+        return Ok(HttpResponse {
+            status,
+            headers,
+            body: decoded.text,
+            body_base64: general_purpose::STANDARD.encode(&bytes),
+            body_size: bytes.len(),
+            content_encoding,
+            wire_size,
+            wire_body_base64,
+            content_type: decoded.content_type,
+            charset: decoded.charset,
+            is_text: decoded.is_text,
+            url,
+            redirects,
+            timing,
+            local_addr,
+            remote_addr,
+            http_version: Some(http_version_name(version)),
+            alpn_protocol: alpn_protocol.flatten(),
+            error: decode_error,
+            assertions: Vec::new(),
+            history_error: None,
+        });
+    }
+}
+
+// Cookie of an HTTP session
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HttpCookie {
+    name: String,
+    value: String,
+    domain: String,
+    path: Option<String>,
+    host_only: Option<bool>,
+    expires: Option<i64>, // unix seconds, None for a session cookie
+    secure: Option<bool>,
+    http_only: Option<bool>,
+    same_site: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HttpSessionInfo {
+    name: String,
+    cookie_count: usize,
+}
+
+#[tauri::command]
+pub fn list_http_sessions(sessions: tauri::State<'_, HttpSessions>) -> Result<Vec<HttpSessionInfo>, String> {
+    let sessions = sessions.0.lock().map_err(|_| "HTTP session state is unavailable".to_string())?;
+    let mut infos: Vec<HttpSessionInfo> = sessions
+        .iter()
+        .map(|(name, session)| HttpSessionInfo {
+            name: name.clone(),
+            cookie_count: session
+                .cookies
+                .lock()
+                .map(|store| store.iter_unexpired().count())
+                .unwrap_or(0),
+        })
+        .collect();
+    infos.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(infos)
+}
+
+#[tauri::command]
+pub fn get_http_session_cookies(
+    sessions: tauri::State<'_, HttpSessions>,
+    session: String,
+) -> Result<Vec<HttpCookie>, String> {
+    sessions.with_cookies(&session, creates_on_lookup(&session), |store| {
+        store
+            .iter_unexpired()
+            .map(|cookie| {
+                let (domain, host_only) = match &cookie.domain {
+                    cookie_store::CookieDomain::HostOnly(domain) => (domain.clone(), true),
+                    cookie_store::CookieDomain::Suffix(domain) => (domain.clone(), false),
+                    _ => (String::new(), true),
+                };
+                HttpCookie {
+                    name: cookie.name().to_string(),
+                    value: cookie.value().to_string(),
+                    domain,
+                    path: Some(String::from(cookie.path.clone())),
+                    host_only: Some(host_only),
+                    expires: match &cookie.expires {
+                        cookie_store::CookieExpiration::AtUtc(at) => Some(at.unix_timestamp()),
+                        cookie_store::CookieExpiration::SessionEnd => None,
+                    },
+                    secure: Some(cookie.secure().unwrap_or(false)),
+                    http_only: Some(cookie.http_only().unwrap_or(false)),
+                    same_site: cookie.same_site().map(|same_site| same_site.to_string()),
+                }
+            })
+            .collect()
+    })
+}
+
+// Add a cookie to a session or replace the one with the same domain, path and name
+#[tauri::command]
+pub fn set_http_session_cookie(
+    sessions: tauri::State<'_, HttpSessions>,
+    session: String,
+    cookie: HttpCookie,
+) -> Result<(), String> {
+    let domain = cookie.domain.trim().trim_start_matches('.').to_lowercase();
+    if cookie.name.trim().is_empty() || domain.is_empty() {
+        return Err("Cookie name and domain are required".to_string());
+    }
+    let path = cookie.path.clone().filter(|path| path.starts_with('/')).unwrap_or_else(|| "/".to_string());
+
+    // Build the equivalent Set-Cookie header and store it as if received from the domain
+    let mut set_cookie = format!("{}={}; Path={}", cookie.name.trim(), cookie.value, path);
+    if !cookie.host_only.unwrap_or(false) {
+        set_cookie.push_str(&format!("; Domain={}", domain));
+    }
+    if let Some(expires) = cookie.expires {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0);
+        set_cookie.push_str(&format!("; Max-Age={}", (expires - now).max(0)));
+    }
+    if cookie.secure.unwrap_or(false) {
+        set_cookie.push_str("; Secure");
+    }
+    if cookie.http_only.unwrap_or(false) {
+        set_cookie.push_str("; HttpOnly");
+    }
+    if let Some(same_site) = cookie.same_site.as_deref().filter(|s| !s.is_empty()) {
+        set_cookie.push_str(&format!("; SameSite={}", same_site));
+    }
+
+    let url = reqwest::Url::parse(&format!("https://{}{}", domain, path))
+        .map_err(|e| format!("Invalid cookie domain or path: {}", e))?;
+
+    sessions.with_cookies(&session, true, |store| {
+        store
+            .parse(&set_cookie, &url)
+            .map(|_| ())
+            .map_err(|e| format!("Invalid cookie: {}", e))
+    })?
+}
+
+#[tauri::command]
+pub fn delete_http_session_cookie(
+    sessions: tauri::State<'_, HttpSessions>,
+    session: String,
+    domain: String,
+    path: String,
+    name: String,
+) -> Result<bool, String> {
+    sessions.with_cookies(&session, creates_on_lookup(&session), |store| store.remove(&domain, &path, &name).is_some())
+}
+
+#[tauri::command]
+pub fn clear_http_session_cookies(sessions: tauri::State<'_, HttpSessions>, session: String) -> Result<(), String> {
+    sessions.with_cookies(&session, creates_on_lookup(&session), |store| store.clear())
+}
+
+// Drop a session together with its connection pool and cookies
+#[tauri::command]
+pub fn remove_http_session(sessions: tauri::State<'_, HttpSessions>, session: String) -> Result<bool, String> {
+    let mut sessions = sessions.0.lock().map_err(|_| "HTTP session state is unavailable".to_string())?;
+    Ok(sessions.remove(&session).is_some())
+}
+
+// Streaming requests in flight, keyed by request id, with the handle used to cancel them
+#[derive(Default)]
+pub struct HttpStreams(pub(crate) Mutex<HashMap<String, std::sync::Arc<tokio::sync::Notify>>>);
+
+pub(crate) static HTTP_STREAM_COUNTER: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(1);
+
+// Payload of the "http-stream-headers" event
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HttpStreamHeaders {
+    request_id: String,
+    status: u16,
+    headers: HashMap<String, String>,
+    url: String,
+    content_type: Option<String>,
+    content_length: Option<u64>,
+}
+
+// Payload of the "http-stream-chunk" event
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HttpStreamChunk {
+    request_id: String,
+    offset: u64,
+    data_base64: String,
+    text: Option<String>, // UTF-8 text of the chunk for textual responses
+}
+
+// Payload of the "http-stream-progress" event
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HttpStreamProgress {
+    request_id: String,
+    received: u64,
+    total: Option<u64>,
+}
+
+// Payload of the "http-stream-end" event
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HttpStreamEnd {
+    request_id: String,
+    received: u64,
+    cancelled: bool,
+    saved_to: Option<String>,
+    timing: Option<HttpTiming>,
+    error: Option<String>,
+}
+
+// Start a request whose response is delivered through events instead of being buffered.
+// When save_to is set the body is written to that file and only progress is emitted.
+#[tauri::command]
+pub async fn http_request_stream(
+    app: tauri::AppHandle,
+    sessions: tauri::State<'_, HttpSessions>,
+    streams: tauri::State<'_, HttpStreams>,
+    secrets: tauri::State<'_, SecretStore>,
+    request: HttpRequest,
+    request_id: Option<String>,
+    save_to: Option<String>,
+) -> Result<String, String> {
+    let mask = secrets.mask(&request);
+    let request = secrets.resolve(request)?;
+    let mut options = request.options.clone().unwrap_or_default();
+    // Redirects are left to the client; hops are not reported on streams
+    options.capture_redirects = None;
+    let session = request.session.as_deref().unwrap_or(DEFAULT_HTTP_SESSION);
+    let client = sessions.client(session, &options)?;
+    let auth = request.auth.clone();
+    let built = build_http_request(&client, request)?
+        .build()
+        .map_err(|e| describe_request_error(&e))?;
+
+    let request_id = request_id
+        .filter(|id| !id.is_empty())
+        .unwrap_or_else(|| format!("http-{}", HTTP_STREAM_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed)));
+    let cancel = std::sync::Arc::new(tokio::sync::Notify::new());
+    {
+        let mut active = streams.0.lock().map_err(|_| "HTTP stream state is unavailable".to_string())?;
+        if active.contains_key(&request_id) {
+            return Err(format!("Request id already in use: {}", request_id));
+        }
+        active.insert(request_id.clone(), cancel.clone());
+    }
+
+    let id = request_id.clone();
+    tokio::spawn(async move {
+        use tauri::{Emitter, Manager};
+
+        let mut end = run_http_stream(&app, &client, built, auth, &mask, &id, save_to, &cancel).await;
+        mask.apply_error(&mut end.error);
+        if let Ok(mut active) = app.state::<HttpStreams>().0.lock() {
+            active.remove(&id);
+        }
+        let _ = app.emit("http-stream-end", end);
+    });
+
+    Ok(request_id)
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn run_http_stream(
+    app: &tauri::AppHandle,
+    client: &reqwest::Client,
+    request: reqwest::Request,
+    auth: Option<RequestAuth>,
+    mask: &SecretMask,
+    request_id: &str,
+    save_to: Option<String>,
+    cancel: &tokio::sync::Notify,
+) -> HttpStreamEnd {
+    use tauri::Emitter;
+    use tokio::io::AsyncWriteExt;
+
+    let mut end = HttpStreamEnd {
+        request_id: request_id.to_string(),
+        received: 0,
+        cancelled: false,
+        saved_to: None,
+        timing: None,
+        error: None,
+    };
+
+    let trace = std::sync::Arc::new(Mutex::new(ConnectionTrace::default()));
+    let started = std::time::Instant::now();
+    let mut response = tokio::select! {
+        result = HTTP_TRACE.scope(trace.clone(), send_http_request(client, request, auth.as_ref())) => match result {
+            Ok(response) => response,
+            Err(e) => {
+                end.error = Some(e);
+                return end;
+            }
+        },
+        _ = cancel.notified() => {
+            end.cancelled = true;
+            return end;
+        }
+    };
+    let headers_at = std::time::Instant::now();
+
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+    let total = response.content_length();
+    let mut headers = header_map_to_hash_map(response.headers());
+    mask.apply_headers(&mut headers);
+    let _ = app.emit(
+        "http-stream-headers",
+        HttpStreamHeaders {
+            request_id: request_id.to_string(),
+            status: response.status().as_u16(),
+            headers,
+            url: mask.apply(response.url().as_str()),
+            content_type: content_type.clone(),
+            content_length: total,
+        },
+    );
+
+    let mut file = match save_to.as_deref() {
+        Some(path) => match tokio::fs::File::create(path).await {
+            Ok(file) => Some(file),
+            Err(e) => {
+                end.error = Some(format!("Failed to create {}: {}", path, e));
+                return end;
+            }
+        },
+        None => None,
+    };
+    let is_text = content_type
+        .as_deref()
+        .map(|header| parse_content_type(header).0)
+        .and_then(|mime| is_text_mime(&mime))
+        .unwrap_or(true);
+    // Bytes of a UTF-8 sequence split across chunks
+    let mut pending_utf8: Vec<u8> = Vec::new();
+
+    loop {
+        let chunk = tokio::select! {
+            chunk = response.chunk() => chunk,
+            _ = cancel.notified() => {
+                end.cancelled = true;
+                break;
+            }
+        };
+        let chunk = match chunk {
+            Ok(Some(chunk)) => chunk,
+            Ok(None) => break,
+            Err(e) => {
+                end.error = Some(describe_request_error(&e));
+                break;
+            }
+        };
+
+        if let Some(file) = file.as_mut() {
+            if let Err(e) = file.write_all(&chunk).await {
+                end.error = Some(format!("Failed to write response body: {}", e));
+                break;
+            }
+        } else {
+            let text = if is_text {
+                pending_utf8.extend_from_slice(&chunk);
+                let valid_up_to = match std::str::from_utf8(&pending_utf8) {
+                    Ok(_) => pending_utf8.len(),
+                    Err(e) if e.error_len().is_none() => e.valid_up_to(),
+                    Err(_) => pending_utf8.len(),
+                };
+                let rest = pending_utf8.split_off(valid_up_to);
+                let text = String::from_utf8_lossy(&pending_utf8).into_owned();
+                pending_utf8 = rest;
+                Some(text)
+            } else {
+                None
+            };
+            let _ = app.emit(
+                "http-stream-chunk",
+                HttpStreamChunk {
+                    request_id: request_id.to_string(),
+                    offset: end.received,
+                    data_base64: general_purpose::STANDARD.encode(&chunk),
+                    text,
+                },
+            );
+        }
+
+        end.received += chunk.len() as u64;
+        let _ = app.emit(
+            "http-stream-progress",
+            HttpStreamProgress {
+                request_id: request_id.to_string(),
+                received: end.received,
+                total,
+            },
+        );
+    }
+
+    if let Some(mut file) = file {
+        if let Err(e) = file.flush().await {
+            end.error.get_or_insert(format!("Failed to write response body: {}", e));
+        }
+        end.saved_to = save_to;
+    }
+    end.timing = trace
+        .lock()
+        .ok()
+        .map(|trace| trace.timing(started, headers_at, std::time::Instant::now()));
+    end
+}
+
+// Abort a streaming request started with http_request_stream
+#[tauri::command]
+pub fn cancel_http_request(streams: tauri::State<'_, HttpStreams>, request_id: String) -> Result<bool, String> {
+    let active = streams.0.lock().map_err(|_| "HTTP stream state is unavailable".to_string())?;
+    match active.get(&request_id) {
+        Some(cancel) => {
+            cancel.notify_one();
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SERVER_DELAY: std::time::Duration = std::time::Duration::from_millis(50);
+
+    // Serves every request with a short delay so the time to first byte is measurable
+    async fn serve_delayed<S>(stream: S)
+    where
+        S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin + Send + 'static,
+    {
+        let service = hyper::service::service_fn(|_request| async {
+            tokio::time::sleep(SERVER_DELAY).await;
+            Ok::<_, std::convert::Infallible>(hyper::Response::new(http_body_util::Full::new(
+                hyper::body::Bytes::from_static(b"ok"),
+            )))
+        });
+        let _ = hyper::server::conn::http1::Builder::new()
+            .serve_connection(hyper_util::rt::TokioIo::new(stream), service)
+            .await;
+    }
+
+    fn test_request(url: String, options: HttpRequestOptions) -> HttpRequest {
+        HttpRequest {
+            url,
+            method: "GET".to_string(),
+            headers: HashMap::new(),
+            body: None,
+            options: Some(options),
+            session: None,
+            auth: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn http_request_reports_connection_timing() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            loop {
+                let (stream, _) = listener.accept().await.unwrap();
+                tokio::spawn(serve_delayed(stream));
+            }
+        });
+
+        // Go through localhost so the request is resolved by TracingResolver
+        let options = HttpRequestOptions::default();
+        let client = build_http_client(&options, None).unwrap();
+        let url = format!("http://localhost:{}/", port);
+        let response = execute_http_request(&client, test_request(url.clone(), options.clone()))
+            .await
+            .unwrap();
+        assert_eq!(response.error, None);
+        assert_eq!(response.status, 200);
+        let timing = response.timing.expect("timing should be reported");
+        assert!(!timing.connection_reused);
+        assert!(timing.dns_ms.is_some());
+        assert!(timing.tcp_connect_ms.is_some());
+        assert_eq!(timing.tls_handshake_ms, None);
+        assert!(timing.ttfb_ms >= SERVER_DELAY.as_secs_f64() * 1000.0);
+        assert!(timing.total_ms >= timing.ttfb_ms);
+
+        // A second request on the same client reuses the pooled connection
+        let response = execute_http_request(&client, test_request(url, options)).await.unwrap();
+        let timing = response.timing.expect("timing should be reported");
+        assert!(timing.connection_reused);
+        assert_eq!(timing.dns_ms, None);
+        assert_eq!(timing.tcp_connect_ms, None);
+    }
+
+    // Guards the kx_hint hook in TracingSessionStore against rustls upgrades
+    #[tokio::test]
+    async fn https_request_reports_tls_handshake() {
+        let _ = rustls::crypto::aws_lc_rs::default_provider().install_default();
+        let certified = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+        let key = rustls::pki_types::PrivateKeyDer::Pkcs8(certified.key_pair.serialize_der().into());
+        let config = rustls::ServerConfig::builder()
+            .with_no_client_auth()
+            .with_single_cert(vec![certified.cert.der().clone()], key)
+            .unwrap();
+        let acceptor = tokio_rustls::TlsAcceptor::from(std::sync::Arc::new(config));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            loop {
+                let (stream, _) = listener.accept().await.unwrap();
+                let acceptor = acceptor.clone();
+                tokio::spawn(async move {
+                    if let Ok(stream) = acceptor.accept(stream).await {
+                        serve_delayed(stream).await;
+                    }
+                });
+            }
+        });
+
+        let options = HttpRequestOptions {
+            ca_cert_pem: Some(certified.cert.pem()),
+            ..Default::default()
+        };
+        let client = build_http_client(&options, None).unwrap();
+        let url = format!("https://localhost:{}/", port);
+        let response = execute_http_request(&client, test_request(url, options)).await.unwrap();
+        assert_eq!(response.error, None);
+        assert_eq!(response.status, 200);
+        let timing = response.timing.expect("timing should be reported");
+        assert!(timing.dns_ms.is_some());
+        assert!(timing.tcp_connect_ms.is_some());
+        assert!(timing.tls_handshake_ms.is_some());
+        assert!(timing.ttfb_ms >= SERVER_DELAY.as_secs_f64() * 1000.0);
+    }
+}
@@ -0,0 +1,1520 @@
+//! Request import from curl, HAR, Postman and OpenAPI
+
+use std::collections::HashMap;
+use std::fs;
+use base64::{Engine as _, engine::general_purpose};
+use serde::{Deserialize, Serialize};
+use crate::collection::{CollectionRequest, json_value_text};
+use crate::http::{ApiKeyLocation, FormField, HttpProtocol, HttpRequest, HttpRequestOptions, MultipartEntry, parse_content_type, parse_http_method, RequestAuth, RequestBody, rfc3986_encode};
+
+// curl and HAR import
+
+// Split a shell command line into words, handling quotes, backslash escapes, $'...'
+// strings and line continuations as produced by "Copy as cURL"
+fn split_shell_words(input: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut in_word = false;
+    let mut chars = input.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('\n') => {}
+                Some('\r') if chars.peek() == Some(&'\n') => {
+                    chars.next();
+                }
+                Some(escaped) => {
+                    word.push(escaped);
+                    in_word = true;
+                }
+                None => {}
+            },
+            '\'' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => word.push(c),
+                        None => return Err("Unterminated single quote".to_string()),
+                    }
+                }
+            }
+            '"' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\' | '$' | '`')) => word.push(c),
+                            Some('\n') => {}
+                            Some(c) => {
+                                word.push('\\');
+                                word.push(c);
+                            }
+                            None => return Err("Unterminated double quote".to_string()),
+                        },
+                        Some(c) => word.push(c),
+                        None => return Err("Unterminated double quote".to_string()),
+                    }
+                }
+            }
+            '$' if chars.peek() == Some(&'\'') => {
+                // ANSI-C quoting; \x escapes may form multi-byte UTF-8 sequences
+                chars.next();
+                in_word = true;
+                let mut bytes = Vec::new();
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some('\\') => {
+                            let escaped = chars.next().ok_or_else(|| "Unterminated $'...' string".to_string())?;
+                            match escaped {
+                                'n' => bytes.push(b'\n'),
+                                't' => bytes.push(b'\t'),
+                                'r' => bytes.push(b'\r'),
+                                'a' => bytes.push(0x07),
+                                'b' => bytes.push(0x08),
+                                'e' | 'E' => bytes.push(0x1b),
+                                'f' => bytes.push(0x0c),
+                                'v' => bytes.push(0x0b),
+                                'x' | 'u' | 'U' => {
+                                    let max_digits = match escaped {
+                                        'x' => 2,
+                                        'u' => 4,
+                                        _ => 8,
+                                    };
+                                    let mut digits = String::new();
+                                    while digits.len() < max_digits {
+                                        match chars.peek() {
+                                            Some(c) if c.is_ascii_hexdigit() => digits.push(chars.next().unwrap_or_default()),
+                                            _ => break,
+                                        }
+                                    }
+                                    let code = u32::from_str_radix(&digits, 16)
+                                        .map_err(|_| format!("Invalid \\{} escape", escaped))?;
+                                    if escaped == 'x' {
+                                        bytes.push(code as u8);
+                                    } else {
+                                        let c = char::from_u32(code).ok_or_else(|| format!("Invalid \\{} escape", escaped))?;
+                                        bytes.extend_from_slice(c.to_string().as_bytes());
+                                    }
+                                }
+                                c => {
+                                    if !matches!(c, '\\' | '\'' | '"' | '?') {
+                                        bytes.push(b'\\');
+                                    }
+                                    bytes.extend_from_slice(c.to_string().as_bytes());
+                                }
+                            }
+                        }
+                        Some(c) => bytes.extend_from_slice(c.to_string().as_bytes()),
+                        None => return Err("Unterminated $'...' string".to_string()),
+                    }
+                }
+                word.push_str(&String::from_utf8_lossy(&bytes));
+            }
+            c if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            c => {
+                word.push(c);
+                in_word = true;
+            }
+        }
+    }
+    if in_word {
+        words.push(word);
+    }
+    Ok(words)
+}
+
+// Options collected from one curl invocation
+#[derive(Default)]
+struct CurlCommand {
+    urls: Vec<String>,
+    method: Option<String>,
+    head: bool,
+    get: bool,
+    headers: Vec<(String, String)>,
+    data: Vec<String>,
+    data_file: Option<String>,
+    upload_file: Option<String>,
+    form: Vec<MultipartEntry>,
+    user: Option<String>,
+    digest: bool,
+    aws_sigv4: Option<String>,
+    bearer: Option<String>,
+    options: HttpRequestOptions,
+    has_options: bool,
+}
+
+// Split "value;name=param;name=\"quoted param\"" as used by curl -F
+fn split_curl_form_value(value: &str) -> (String, Vec<(String, String)>) {
+    let mut segments = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => quoted = !quoted,
+            '\\' if quoted => {
+                if let Some(escaped) = chars.next() {
+                    current.push(escaped);
+                }
+            }
+            ';' if !quoted => segments.push(std::mem::take(&mut current)),
+            c => current.push(c),
+        }
+    }
+    segments.push(current);
+
+    let mut segments = segments.into_iter();
+    let content = segments.next().unwrap_or_default();
+    let params = segments
+        .filter_map(|segment| {
+            let (name, value) = segment.split_once('=')?;
+            Some((name.trim().to_ascii_lowercase(), value.to_string()))
+        })
+        .collect();
+    (content, params)
+}
+
+fn parse_curl_form(field: &str, literal: bool) -> Result<MultipartEntry, String> {
+    let (key, value) = field
+        .split_once('=')
+        .ok_or_else(|| format!("Invalid form field '{}': expected name=value", field))?;
+    let mut entry = MultipartEntry {
+        key: key.to_string(),
+        value: value.to_string(),
+        r#type: "text".to_string(),
+        filename: None,
+        path: None,
+        content_type: None,
+        headers: None,
+    };
+    if literal {
+        return Ok(entry);
+    }
+    if value.starts_with('<') {
+        return Err(format!("Form field '{}' reads its value from a file (<), which is not supported", key));
+    }
+
+    let (content, params) = split_curl_form_value(value);
+    let mut headers = HashMap::new();
+    for (name, param) in params {
+        match name.as_str() {
+            "type" => entry.content_type = Some(param),
+            "filename" => entry.filename = Some(param),
+            "headers" => {
+                if let Some((header, header_value)) = param.split_once(':') {
+                    headers.insert(header.trim().to_string(), header_value.trim().to_string());
+                } else {
+                    return Err(format!("Form field '{}' reads headers from a file, which is not supported", key));
+                }
+            }
+            _ => {}
+        }
+    }
+    if !headers.is_empty() {
+        entry.headers = Some(headers);
+    }
+    match content.strip_prefix('@') {
+        Some(path) => {
+            entry.r#type = "file".to_string();
+            entry.path = Some(path.to_string());
+            entry.value = String::new();
+        }
+        None => entry.value = content,
+    }
+    Ok(entry)
+}
+
+// --data-urlencode: "content", "=content" or "name=content" with content percent-encoded
+fn curl_urlencode_data(data: &str) -> Result<String, String> {
+    if let Some((name, _)) = data.split_once('@').filter(|(name, _)| !name.contains('=')) {
+        return Err(format!(
+            "--data-urlencode {}@file reads from a file, which is not supported",
+            name
+        ));
+    }
+    Ok(match data.split_once('=') {
+        Some(("", content)) => rfc3986_encode(content),
+        Some((name, content)) => format!("{}={}", name, rfc3986_encode(content)),
+        None => rfc3986_encode(data),
+    })
+}
+
+// Body for -d data, shaped by the Content-Type curl would send
+fn curl_data_body(data: String, content_type: Option<&str>) -> RequestBody {
+    let mime = content_type
+        .map(|ct| parse_content_type(ct).0)
+        .unwrap_or_else(|| "application/x-www-form-urlencoded".to_string());
+    if mime == "application/json" || mime.ends_with("+json") {
+        return RequestBody::Json { content: data };
+    }
+    if mime == "application/x-www-form-urlencoded" && data.split('&').all(|pair| pair.contains('=')) {
+        let decode = |part: &str| {
+            percent_encoding::percent_decode_str(&part.replace('+', " "))
+                .decode_utf8_lossy()
+                .into_owned()
+        };
+        let fields = data
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .map(|(key, value)| FormField { key: decode(key), value: decode(value) })
+            .collect();
+        return RequestBody::Urlencoded { fields };
+    }
+    RequestBody::Text {
+        content: data,
+        // curl sends form data by default
+        content_type: content_type.is_none().then_some(mime),
+    }
+}
+
+fn apply_curl_option(command: &mut CurlCommand, option: &str, value: Option<String>) -> Result<(), String> {
+    let value = || value.clone().ok_or_else(|| format!("Option {} requires a value", option));
+    match option {
+        "-X" | "--request" => command.method = Some(value()?.to_uppercase()),
+        "-H" | "--header" => {
+            let header = value()?;
+            if let Some((key, header_value)) = header.split_once(':') {
+                let key = key.trim();
+                if header_value.trim().is_empty() {
+                    // "Name:" removes the header in curl, including earlier -H values
+                    command.headers.retain(|(name, _)| !name.eq_ignore_ascii_case(key));
+                } else {
+                    command.headers.push((key.to_string(), header_value.trim().to_string()));
+                }
+            } else if let Some(key) = header.strip_suffix(';') {
+                command.headers.push((key.trim().to_string(), String::new()));
+            } else {
+                return Err(format!("Invalid header '{}'", header));
+            }
+        }
+        "-d" | "--data" | "--data-ascii" | "--data-binary" => {
+            let data = value()?;
+            match data.strip_prefix('@') {
+                Some(path) if option == "--data-binary" || option == "-d" || option == "--data" => {
+                    if command.data_file.is_some() || !command.data.is_empty() {
+                        return Err("Combining @file data with other data is not supported".to_string());
+                    }
+                    command.data_file = Some(path.to_string());
+                }
+                _ => command.data.push(data),
+            }
+        }
+        "--data-raw" => command.data.push(value()?),
+        "--data-urlencode" => command.data.push(curl_urlencode_data(&value()?)?),
+        "--json" => {
+            command.data.push(value()?);
+            for (key, header_value) in [("Content-Type", "application/json"), ("Accept", "application/json")] {
+                if !command.headers.iter().any(|(name, _)| name.eq_ignore_ascii_case(key)) {
+                    command.headers.push((key.to_string(), header_value.to_string()));
+                }
+            }
+        }
+        "-F" | "--form" => command.form.push(parse_curl_form(&value()?, false)?),
+        "--form-string" => command.form.push(parse_curl_form(&value()?, true)?),
+        "-T" | "--upload-file" => command.upload_file = Some(value()?),
+        "-u" | "--user" => command.user = Some(value()?),
+        "--digest" => command.digest = true,
+        "--basic" => command.digest = false,
+        "--aws-sigv4" => command.aws_sigv4 = Some(value()?),
+        "--oauth2-bearer" => command.bearer = Some(value()?),
+        "-A" | "--user-agent" => command.headers.push(("User-Agent".to_string(), value()?)),
+        "-e" | "--referer" => command.headers.push(("Referer".to_string(), value()?)),
+        "-b" | "--cookie" => {
+            let cookie = value()?;
+            if !cookie.contains('=') {
+                return Err(format!("Reading cookies from a file ({}) is not supported", cookie));
+            }
+            command.headers.push(("Cookie".to_string(), cookie));
+        }
+        "--url" => command.urls.push(value()?),
+        "-G" | "--get" => command.get = true,
+        "-I" | "--head" => command.head = true,
+        "-k" | "--insecure" => {
+            command.options.insecure = Some(true);
+            command.has_options = true;
+        }
+        "-L" | "--location" => {}
+        "--max-redirs" => {
+            let max = value()?;
+            command.options.max_redirects = Some(
+                max.parse::<i64>()
+                    .map_err(|_| format!("Invalid --max-redirs value: {}", max))?
+                    .max(0) as usize,
+            );
+            command.has_options = true;
+        }
+        "-x" | "--proxy" => {
+            command.options.proxy_url = Some(value()?);
+            command.has_options = true;
+        }
+        "-m" | "--max-time" => {
+            let secs = value()?;
+            let secs: f64 = secs.parse().map_err(|_| format!("Invalid --max-time value: {}", secs))?;
+            command.options.timeout_ms = Some((secs * 1000.0) as u64);
+            command.has_options = true;
+        }
+        "--cacert" => {
+            let path = value()?;
+            let pem = fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+            command.options.ca_cert_pem = Some(pem);
+            command.has_options = true;
+        }
+        "--compressed" => {
+            command.options.decompress = Some(true);
+            command.has_options = true;
+        }
+        // Output and diagnostics only
+        "-s" | "--silent" | "-S" | "--show-error" | "-v" | "--verbose" | "-i" | "--include" | "-f" | "--fail"
+        | "--fail-with-body" | "-g" | "--globoff" | "-N" | "--no-buffer" | "-#" | "--progress-bar"
+        | "--no-progress-meter" => {}
+        "--http1.0" | "--http1.1" => {
+            command.options.protocol = Some(HttpProtocol::Http1);
+            command.has_options = true;
+        }
+        "--http2-prior-knowledge" => {
+            command.options.protocol = Some(HttpProtocol::Http2);
+            command.has_options = true;
+        }
+        // curl offers h2 through ALPN by default, as the client does
+        "--http2" => {}
+        "-o" | "--output" | "-w" | "--write-out" | "--connect-timeout" => {
+            value()?;
+        }
+        _ => return Err(format!("Unsupported curl option: {}", option)),
+    }
+    Ok(())
+}
+
+// Whether a curl option consumes the next word as its value
+fn curl_option_takes_value(option: &str) -> bool {
+    matches!(
+        option,
+        "-X" | "--request"
+            | "-H"
+            | "--header"
+            | "-d"
+            | "--data"
+            | "--data-ascii"
+            | "--data-binary"
+            | "--data-raw"
+            | "--data-urlencode"
+            | "--json"
+            | "-F"
+            | "--form"
+            | "--form-string"
+            | "-T"
+            | "--upload-file"
+            | "-u"
+            | "--user"
+            | "--aws-sigv4"
+            | "--oauth2-bearer"
+            | "-A"
+            | "--user-agent"
+            | "-e"
+            | "--referer"
+            | "-b"
+            | "--cookie"
+            | "--url"
+            | "--max-redirs"
+            | "-x"
+            | "--proxy"
+            | "-m"
+            | "--max-time"
+            | "--cacert"
+            | "-o"
+            | "--output"
+            | "-w"
+            | "--write-out"
+            | "--connect-timeout"
+    )
+}
+
+fn parse_curl_words(words: Vec<String>) -> Result<Vec<CurlCommand>, String> {
+    let mut commands: Vec<CurlCommand> = Vec::new();
+    let mut words = words.into_iter();
+    while let Some(word) = words.next() {
+        if word == "curl" {
+            commands.push(CurlCommand::default());
+            continue;
+        }
+        let command = commands
+            .last_mut()
+            .ok_or_else(|| "Not a curl command: it must start with 'curl'".to_string())?;
+
+        if word.starts_with("--") && word.len() > 2 {
+            let value = if curl_option_takes_value(&word) { words.next() } else { None };
+            apply_curl_option(command, &word, value)?;
+        } else if word.starts_with('-') && word.len() > 1 {
+            // Short options may be combined (-sSL) or carry their value (-XPOST)
+            let flags: Vec<char> = word[1..].chars().collect();
+            for (i, flag) in flags.iter().enumerate() {
+                let option = format!("-{}", flag);
+                if curl_option_takes_value(&option) {
+                    let rest: String = flags[i + 1..].iter().collect();
+                    let value = if rest.is_empty() { words.next() } else { Some(rest) };
+                    apply_curl_option(command, &option, value)?;
+                    break;
+                }
+                apply_curl_option(command, &option, None)?;
+            }
+        } else {
+            command.urls.push(word);
+        }
+    }
+    if commands.is_empty() {
+        return Err("Not a curl command: it must start with 'curl'".to_string());
+    }
+    Ok(commands)
+}
+
+fn curl_command_requests(command: CurlCommand, warnings: &mut Vec<String>) -> Result<Vec<HttpRequest>, String> {
+    if command.urls.is_empty() {
+        return Err("The curl command has no URL".to_string());
+    }
+    if !command.form.is_empty() && (!command.data.is_empty() || command.data_file.is_some()) {
+        return Err("Cannot combine -F form fields with -d data".to_string());
+    }
+    if let Some(path) = command.data_file.as_deref().filter(|_| command.get) {
+        push_warning(
+            warnings,
+            format!("-G with -d @{} would put the file content in the query string; the data was left out", path),
+        );
+    }
+
+    let mut headers: HashMap<String, String> = HashMap::new();
+    for (key, value) in command.headers {
+        let existing = headers.keys().find(|name| name.eq_ignore_ascii_case(&key)).cloned();
+        match existing {
+            Some(name) => {
+                let separator = if key.eq_ignore_ascii_case("cookie") { "; " } else { ", " };
+                if let Some(joined) = headers.get_mut(&name) {
+                    joined.push_str(separator);
+                    joined.push_str(&value);
+                }
+            }
+            None => {
+                headers.insert(key, value);
+            }
+        }
+    }
+    let content_type = headers
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case("content-type"))
+        .map(|(_, value)| value.clone());
+
+    let query = (command.get && !command.data.is_empty()).then(|| command.data.join("&"));
+    let uploads = command.upload_file.is_some();
+    let body = if let Some(path) = command.upload_file {
+        Some(RequestBody::Binary { path: Some(path), base64: None, content_type: None })
+    } else if !command.form.is_empty() {
+        Some(RequestBody::Multipart { entries: command.form })
+    } else if let Some(path) = command.data_file.filter(|_| !command.get) {
+        Some(RequestBody::Binary { path: Some(path), base64: None, content_type: None })
+    } else if !command.data.is_empty() && !command.get {
+        Some(curl_data_body(command.data.join("&"), content_type.as_deref()))
+    } else {
+        None
+    };
+
+    let method = match command.method {
+        Some(method) => method,
+        None if command.head => "HEAD".to_string(),
+        None if command.get => "GET".to_string(),
+        None if uploads => "PUT".to_string(),
+        None if body.is_some() => "POST".to_string(),
+        None => "GET".to_string(),
+    };
+    parse_http_method(&method)?;
+
+    let auth = match (command.user, command.aws_sigv4, command.bearer) {
+        (Some(user), Some(provider), _) => {
+            // --aws-sigv4 "aws:amz:<region>:<service>" with the keys given by -u
+            let parts: Vec<&str> = provider.split(':').collect();
+            let (region, service) = match parts.as_slice() {
+                [_, _, region, service, ..] => (region.to_string(), service.to_string()),
+                _ => return Err(format!("Unsupported --aws-sigv4 value '{}': expected aws:amz:region:service", provider)),
+            };
+            let (access_key_id, secret_access_key) = user.split_once(':').unwrap_or((user.as_str(), ""));
+            Some(RequestAuth::AwsSigv4 {
+                access_key_id: access_key_id.to_string(),
+                secret_access_key: secret_access_key.to_string(),
+                session_token: None,
+                region,
+                service,
+            })
+        }
+        (None, Some(_), _) => return Err("--aws-sigv4 requires the keys to be given with -u".to_string()),
+        (Some(user), None, _) => {
+            let (username, password) = match user.split_once(':') {
+                Some((username, password)) => (username.to_string(), Some(password.to_string())),
+                None => (user, None),
+            };
+            if command.digest {
+                Some(RequestAuth::Digest { username, password: password.unwrap_or_default() })
+            } else {
+                Some(RequestAuth::Basic { username, password })
+            }
+        }
+        (None, None, Some(token)) => Some(RequestAuth::Bearer { token }),
+        (None, None, None) => None,
+    };
+
+    let options = command.has_options.then_some(command.options);
+    Ok(command
+        .urls
+        .into_iter()
+        .map(|url| {
+            let mut url = if url.contains("://") { url } else { format!("http://{}", url) };
+            if let Some(query) = &query {
+                url.push(if url.contains('?') { '&' } else { '?' });
+                url.push_str(query);
+            }
+            HttpRequest {
+                url,
+                method: method.clone(),
+                headers: headers.clone(),
+                body: body.clone(),
+                options: options.clone(),
+                session: None,
+                auth: auth.clone(),
+            }
+        })
+        .collect())
+}
+
+// Requests parsed from curl command lines, with the options that could not be converted
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CurlImport {
+    requests: Vec<HttpRequest>,
+    warnings: Vec<String>,
+}
+
+// Parse one or more curl command lines into requests
+#[tauri::command]
+pub fn import_curl_command(command: String) -> Result<CurlImport, String> {
+    let mut requests = Vec::new();
+    let mut warnings = Vec::new();
+    for command in parse_curl_words(split_shell_words(&command)?)? {
+        requests.extend(curl_command_requests(command, &mut warnings)?);
+    }
+    Ok(CurlImport { requests, warnings })
+}
+
+// HAR 1.2 archive, only the parts needed to rebuild requests
+#[derive(Deserialize)]
+struct HarArchive {
+    log: HarLog,
+}
+
+#[derive(Deserialize)]
+struct HarLog {
+    #[serde(default)]
+    entries: Vec<HarEntry>,
+}
+
+#[derive(Deserialize)]
+struct HarEntry {
+    request: HarRequest,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HarRequest {
+    method: String,
+    url: String,
+    #[serde(default)]
+    headers: Vec<HarNameValue>,
+    post_data: Option<HarPostData>,
+}
+
+#[derive(Deserialize)]
+struct HarNameValue {
+    name: String,
+    value: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HarPostData {
+    mime_type: Option<String>,
+    text: Option<String>,
+    #[serde(default)]
+    params: Vec<HarParam>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HarParam {
+    name: String,
+    value: Option<String>,
+    file_name: Option<String>,
+    content_type: Option<String>,
+}
+
+fn har_request(request: HarRequest) -> Result<HttpRequest, String> {
+    let method = request.method.to_uppercase();
+    parse_http_method(&method)?;
+
+    let mut headers: HashMap<String, String> = HashMap::new();
+    for header in request.headers {
+        // HTTP/2 pseudo headers and framing headers are recomputed when sending
+        let name = header.name;
+        if name.starts_with(':') || name.eq_ignore_ascii_case("content-length") || name.eq_ignore_ascii_case("host") {
+            continue;
+        }
+        match headers.keys().find(|key| key.eq_ignore_ascii_case(&name)).cloned() {
+            Some(existing) => {
+                let separator = if name.eq_ignore_ascii_case("cookie") { "; " } else { ", " };
+                if let Some(joined) = headers.get_mut(&existing) {
+                    joined.push_str(separator);
+                    joined.push_str(&header.value);
+                }
+            }
+            None => {
+                headers.insert(name, header.value);
+            }
+        }
+    }
+
+    let body = request.post_data.and_then(|post_data| {
+        let mime = post_data
+            .mime_type
+            .as_deref()
+            .map(|mime| parse_content_type(mime).0)
+            .unwrap_or_default();
+        if mime == "multipart/form-data" && !post_data.params.is_empty() {
+            let entries = post_data
+                .params
+                .into_iter()
+                .map(|param| {
+                    let value = param.value.unwrap_or_default();
+                    // File parts carry their content base64 encoded, HAR keeps it as text
+                    let (r#type, value) = match param.file_name {
+                        Some(_) => ("file", general_purpose::STANDARD.encode(value)),
+                        None => ("text", value),
+                    };
+                    MultipartEntry {
+                        key: param.name,
+                        value,
+                        r#type: r#type.to_string(),
+                        filename: param.file_name,
+                        path: None,
+                        content_type: param.content_type,
+                        headers: None,
+                    }
+                })
+                .collect();
+            return Some(RequestBody::Multipart { entries });
+        }
+        if mime == "application/x-www-form-urlencoded" && !post_data.params.is_empty() {
+            let fields = post_data
+                .params
+                .into_iter()
+                .map(|param| FormField { key: param.name, value: param.value.unwrap_or_default() })
+                .collect();
+            return Some(RequestBody::Urlencoded { fields });
+        }
+        let text = post_data.text?;
+        if mime == "multipart/form-data" || mime == "application/x-www-form-urlencoded" {
+            // Keep the original encoding, including the multipart boundary
+            return Some(RequestBody::Text { content: text, content_type: post_data.mime_type });
+        }
+        if mime == "application/json" || mime.ends_with("+json") {
+            return Some(RequestBody::Json { content: text });
+        }
+        Some(RequestBody::Text { content: text, content_type: post_data.mime_type })
+    });
+
+    Ok(HttpRequest {
+        url: request.url,
+        method,
+        headers,
+        body,
+        options: None,
+        session: None,
+        auth: None,
+    })
+}
+
+// Parse a HAR 1.2 archive into its requests, in recorded order
+#[tauri::command]
+pub fn import_har(content: String) -> Result<Vec<HttpRequest>, String> {
+    let archive: HarArchive = serde_json::from_str(&content).map_err(|e| format!("Invalid HAR file: {}", e))?;
+    archive
+        .log
+        .entries
+        .into_iter()
+        .enumerate()
+        .map(|(i, entry)| har_request(entry.request).map_err(|e| format!("HAR entry {}: {}", i + 1, e)))
+        .collect()
+}
+
+// Postman and OpenAPI import
+
+// Requests generated from a Postman collection or an OpenAPI document. Postman
+// {{variables}} are kept and their collection values returned in variables.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ImportedCollection {
+    name: String,
+    requests: Vec<CollectionRequest>,
+    variables: HashMap<String, String>,
+    warnings: Vec<String>, // parts that could not be converted
+}
+
+fn push_warning(warnings: &mut Vec<String>, warning: String) {
+    if !warnings.contains(&warning) {
+        warnings.push(warning);
+    }
+}
+
+// Strings as-is, numbers and booleans as text, anything else empty
+fn value_text(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::Null | serde_json::Value::Array(_) | serde_json::Value::Object(_) => String::new(),
+        other => json_value_text(other),
+    }
+}
+
+fn postman_enabled(entry: &serde_json::Value) -> bool {
+    !entry["disabled"].as_bool().unwrap_or(false)
+}
+
+// Auth parameters: a list of {key, value} in v2.1, a plain object in v2.0
+fn postman_auth_params(auth: &serde_json::Value, kind: &str) -> HashMap<String, String> {
+    match &auth[kind] {
+        serde_json::Value::Array(params) => params
+            .iter()
+            .filter_map(|param| Some((param["key"].as_str()?.to_string(), value_text(&param["value"]))))
+            .collect(),
+        serde_json::Value::Object(params) => params.iter().map(|(key, value)| (key.clone(), value_text(value))).collect(),
+        _ => HashMap::new(),
+    }
+}
+
+// None when the item inherits its parent's auth, Some(None) for "No Auth"
+fn postman_auth(auth: &serde_json::Value, warnings: &mut Vec<String>) -> Option<Option<RequestAuth>> {
+    let kind = auth["type"].as_str()?;
+    let params = postman_auth_params(auth, kind);
+    let param = |key: &str| params.get(key).cloned().unwrap_or_default();
+    let optional = |key: &str| params.get(key).cloned().filter(|value| !value.is_empty());
+    Some(match kind {
+        "noauth" => None,
+        "basic" => Some(RequestAuth::Basic { username: param("username"), password: optional("password") }),
+        "bearer" => Some(RequestAuth::Bearer { token: param("token") }),
+        "apikey" => Some(RequestAuth::ApiKey {
+            key: params.get("key").cloned().unwrap_or_else(|| "X-API-Key".to_string()),
+            value: param("value"),
+            location: if param("in") == "query" { ApiKeyLocation::Query } else { ApiKeyLocation::Header },
+        }),
+        "digest" => Some(RequestAuth::Digest { username: param("username"), password: param("password") }),
+        "awsv4" => Some(RequestAuth::AwsSigv4 {
+            access_key_id: param("accessKey"),
+            secret_access_key: param("secretKey"),
+            session_token: optional("sessionToken"),
+            region: optional("region").unwrap_or_else(|| "us-east-1".to_string()),
+            service: param("service"),
+        }),
+        "oauth1" if optional("signatureMethod").is_none_or(|method| method == "HMAC-SHA1") => Some(RequestAuth::Oauth1 {
+            consumer_key: param("consumerKey"),
+            consumer_secret: param("consumerSecret"),
+            token: optional("token"),
+            token_secret: optional("tokenSecret"),
+            realm: optional("realm"),
+            callback: optional("callback"),
+            verifier: optional("verifier"),
+        }),
+        other => {
+            push_warning(warnings, format!("Postman auth type '{}' is not supported and was left out", other));
+            None
+        }
+    })
+}
+
+// Request URL with :path variables replaced by their value, or by {{name}} when empty
+fn postman_url(url: &serde_json::Value) -> String {
+    let (raw, variables) = match url {
+        serde_json::Value::String(raw) => (raw.clone(), Vec::new()),
+        serde_json::Value::Object(_) => {
+            let raw = url["raw"].as_str().map(str::to_string).unwrap_or_else(|| {
+                let host = match &url["host"] {
+                    serde_json::Value::Array(parts) => parts.iter().map(value_text).collect::<Vec<_>>().join("."),
+                    other => value_text(other),
+                };
+                let path = match &url["path"] {
+                    serde_json::Value::Array(parts) => parts.iter().map(value_text).collect::<Vec<_>>().join("/"),
+                    other => value_text(other),
+                };
+                let mut raw = match url["protocol"].as_str() {
+                    Some(protocol) => format!("{}://{}", protocol, host),
+                    None => host,
+                };
+                if let Some(port) = url["port"].as_str() {
+                    raw.push_str(&format!(":{}", port));
+                }
+                if !path.is_empty() {
+                    raw.push('/');
+                    raw.push_str(&path);
+                }
+                let query: Vec<String> = url["query"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter(|param| postman_enabled(param))
+                    .map(|param| format!("{}={}", value_text(&param["key"]), value_text(&param["value"])))
+                    .collect();
+                if !query.is_empty() {
+                    raw.push('?');
+                    raw.push_str(&query.join("&"));
+                }
+                raw
+            });
+            let variables: Vec<(String, String)> = url["variable"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|variable| Some((variable["key"].as_str()?.to_string(), value_text(&variable["value"]))))
+                .collect();
+            (raw, variables)
+        }
+        _ => (String::new(), Vec::new()),
+    };
+
+    let (path, query) = match raw.split_once('?') {
+        Some((path, query)) => (path.to_string(), Some(query.to_string())),
+        None => (raw, None),
+    };
+    let path = path
+        .split('/')
+        .map(|segment| match segment.strip_prefix(':').filter(|name| !name.is_empty()) {
+            // Values are encoded into the segment, {{variable}} references are kept for substitution
+            Some(name) => variables
+                .iter()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.clone())
+                .filter(|value| !value.is_empty())
+                .map(|value| if value.contains("{{") { value } else { rfc3986_encode(&value) })
+                .unwrap_or_else(|| format!("{{{{{}}}}}", name)),
+            None => segment.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("/");
+    match query {
+        Some(query) => format!("{}?{}", path, query),
+        None => path,
+    }
+}
+
+fn postman_body(body: &serde_json::Value, name: &str, warnings: &mut Vec<String>) -> Option<RequestBody> {
+    if body["disabled"].as_bool().unwrap_or(false) {
+        return None;
+    }
+    match body["mode"].as_str()? {
+        "raw" => {
+            let content = body["raw"].as_str().unwrap_or_default().to_string();
+            if content.is_empty() {
+                return None;
+            }
+            let content_type = match body["options"]["raw"]["language"].as_str() {
+                Some("json") => return Some(RequestBody::Json { content }),
+                Some("xml") => Some("application/xml".to_string()),
+                Some("html") => Some("text/html".to_string()),
+                Some("javascript") => Some("application/javascript".to_string()),
+                _ => None,
+            };
+            Some(RequestBody::Text { content, content_type })
+        }
+        "urlencoded" => Some(RequestBody::Urlencoded {
+            fields: body["urlencoded"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter(|field| postman_enabled(field))
+                .map(|field| FormField { key: value_text(&field["key"]), value: value_text(&field["value"]) })
+                .collect(),
+        }),
+        "formdata" => Some(RequestBody::Multipart {
+            entries: body["formdata"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter(|field| postman_enabled(field))
+                .map(|field| {
+                    let is_file = field["type"].as_str() == Some("file");
+                    let src = match &field["src"] {
+                        serde_json::Value::Array(paths) => paths.first().map(value_text),
+                        other => Some(value_text(other)),
+                    }
+                    .filter(|path| !path.is_empty());
+                    MultipartEntry {
+                        key: value_text(&field["key"]),
+                        value: if is_file { String::new() } else { value_text(&field["value"]) },
+                        r#type: if is_file { "file" } else { "text" }.to_string(),
+                        filename: None,
+                        path: if is_file { src } else { None },
+                        content_type: field["contentType"].as_str().map(str::to_string),
+                        headers: None,
+                    }
+                })
+                .collect(),
+        }),
+        "file" => Some(RequestBody::Binary {
+            path: body["file"]["src"].as_str().map(str::to_string),
+            base64: None,
+            content_type: None,
+        }),
+        "graphql" => {
+            let variables = body["graphql"]["variables"]
+                .as_str()
+                .filter(|variables| !variables.trim().is_empty())
+                .and_then(|variables| match serde_json::from_str(variables) {
+                    Ok(variables) => Some(variables),
+                    Err(e) => {
+                        push_warning(warnings, format!("{}: GraphQL variables are not valid JSON and were left out: {}", name, e));
+                        None
+                    }
+                });
+            Some(RequestBody::Graphql {
+                query: body["graphql"]["query"].as_str().unwrap_or_default().to_string(),
+                variables,
+                operation_name: None,
+            })
+        }
+        _ => None,
+    }
+}
+
+fn postman_items(
+    items: &serde_json::Value,
+    folder: &[String],
+    inherited_auth: &Option<RequestAuth>,
+    requests: &mut Vec<CollectionRequest>,
+    warnings: &mut Vec<String>,
+) {
+    for item in items.as_array().into_iter().flatten() {
+        let name = item["name"].as_str().unwrap_or("Untitled").to_string();
+        if item.get("event").is_some_and(|events| events.as_array().is_some_and(|events| !events.is_empty())) {
+            push_warning(warnings, "Pre-request and test scripts are not imported".to_string());
+        }
+        if item.get("item").is_some() {
+            let auth = postman_auth(&item["auth"], warnings).unwrap_or_else(|| inherited_auth.clone());
+            let mut path = folder.to_vec();
+            path.push(name);
+            postman_items(&item["item"], &path, &auth, requests, warnings);
+            continue;
+        }
+
+        let request = &item["request"];
+        let full_name = folder.iter().chain([&name]).cloned().collect::<Vec<_>>().join(" / ");
+        let (method, url) = match request {
+            serde_json::Value::String(url) => ("GET".to_string(), url.clone()),
+            _ => (
+                request["method"].as_str().unwrap_or("GET").to_uppercase(),
+                postman_url(&request["url"]),
+            ),
+        };
+        if let Err(e) = parse_http_method(&method) {
+            push_warning(warnings, format!("{}: {}", full_name, e));
+            continue;
+        }
+
+        let headers = request["header"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter(|header| postman_enabled(header))
+            .filter_map(|header| Some((header["key"].as_str()?.to_string(), value_text(&header["value"]))))
+            .collect();
+        let auth = postman_auth(&request["auth"], warnings).unwrap_or_else(|| inherited_auth.clone());
+        let body = postman_body(&request["body"], &full_name, warnings);
+
+        requests.push(CollectionRequest {
+            name: Some(full_name),
+            request: HttpRequest {
+                url,
+                method,
+                headers,
+                body,
+                options: None,
+                session: None,
+                auth,
+            },
+            extract: None,
+            assertions: None,
+        });
+    }
+}
+
+// Parse a Postman v2.0/v2.1 collection into request templates
+#[tauri::command]
+pub fn import_postman_collection(content: String) -> Result<ImportedCollection, String> {
+    let collection: serde_json::Value =
+        serde_json::from_str(&content).map_err(|e| format!("Invalid Postman collection: {}", e))?;
+    if !collection["info"].is_object() || !collection["item"].is_array() {
+        return Err("Not a Postman collection: expected 'info' and 'item'".to_string());
+    }
+
+    let mut warnings = Vec::new();
+    let mut requests = Vec::new();
+    let auth = postman_auth(&collection["auth"], &mut warnings).flatten();
+    postman_items(&collection["item"], &[], &auth, &mut requests, &mut warnings);
+
+    let variables = collection["variable"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|variable| postman_enabled(variable))
+        .filter_map(|variable| Some((variable["key"].as_str()?.to_string(), value_text(&variable["value"]))))
+        .collect();
+
+    Ok(ImportedCollection {
+        name: collection["info"]["name"].as_str().unwrap_or("Postman collection").to_string(),
+        requests,
+        variables,
+        warnings,
+    })
+}
+
+// Follow local "#/..." references; external references are returned unresolved
+fn resolve_openapi_ref<'a>(root: &'a serde_json::Value, value: &'a serde_json::Value) -> &'a serde_json::Value {
+    let mut current = value;
+    for _ in 0..32 {
+        let Some(reference) = current.get("$ref").and_then(serde_json::Value::as_str) else {
+            return current;
+        };
+        let pointer = percent_encoding::percent_decode_str(reference.strip_prefix('#').unwrap_or_default()).decode_utf8_lossy();
+        match root.pointer(&pointer) {
+            Some(target) => current = target,
+            None => return current,
+        }
+    }
+    current
+}
+
+// Example value given by the document itself: example, examples, default, const or enum
+fn openapi_given_example(root: &serde_json::Value, value: &serde_json::Value) -> Option<serde_json::Value> {
+    // Parameters and media types nest their schema; a chain that loops back ends the search
+    let mut value = resolve_openapi_ref(root, value);
+    for _ in 0..32 {
+        if let Some(example) = openapi_own_example(root, value) {
+            return Some(example);
+        }
+        value = resolve_openapi_ref(root, value.get("schema")?);
+    }
+    None
+}
+
+fn openapi_own_example(root: &serde_json::Value, value: &serde_json::Value) -> Option<serde_json::Value> {
+    if let Some(example) = value.get("example") {
+        return Some(example.clone());
+    }
+    match value.get("examples") {
+        Some(serde_json::Value::Array(examples)) if !examples.is_empty() => return Some(examples[0].clone()),
+        Some(serde_json::Value::Object(examples)) => {
+            if let Some(example) = examples.values().next() {
+                let example = resolve_openapi_ref(root, example);
+                if let Some(value) = example.get("value") {
+                    return Some(value.clone());
+                }
+            }
+        }
+        _ => {}
+    }
+    for key in ["default", "const"] {
+        if let Some(value) = value.get(key) {
+            return Some(value.clone());
+        }
+    }
+    value
+        .get("enum")
+        .and_then(|values| values.as_array()?.first())
+        .cloned()
+}
+
+// Example instance of a schema, generated from its types when none is given. refs holds the
+// references being expanded, so a schema that contains itself (a tree node) stops there.
+fn openapi_schema_example(
+    root: &serde_json::Value,
+    schema: &serde_json::Value,
+    depth: usize,
+    refs: &mut Vec<String>,
+) -> serde_json::Value {
+    if depth > 8 {
+        return serde_json::Value::Null;
+    }
+    let reference = schema.get("$ref").and_then(serde_json::Value::as_str).map(str::to_string);
+    if reference.as_ref().is_some_and(|reference| refs.contains(reference)) {
+        return serde_json::Value::Null;
+    }
+    let expanding = reference.is_some();
+    refs.extend(reference);
+    let example = openapi_generated_example(root, resolve_openapi_ref(root, schema), depth, refs);
+    if expanding {
+        refs.pop();
+    }
+    example
+}
+
+fn openapi_generated_example(
+    root: &serde_json::Value,
+    schema: &serde_json::Value,
+    depth: usize,
+    refs: &mut Vec<String>,
+) -> serde_json::Value {
+    if let Some(example) = openapi_given_example(root, schema) {
+        return example;
+    }
+    if let Some(parts) = schema["allOf"].as_array() {
+        let mut merged = serde_json::Map::new();
+        for part in parts {
+            if let serde_json::Value::Object(fields) = openapi_schema_example(root, part, depth + 1, refs) {
+                merged.extend(fields);
+            }
+        }
+        return serde_json::Value::Object(merged);
+    }
+    for key in ["oneOf", "anyOf"] {
+        if let Some(first) = schema[key].as_array().and_then(|options| options.first()) {
+            return openapi_schema_example(root, first, depth + 1, refs);
+        }
+    }
+
+    let kind = match &schema["type"] {
+        serde_json::Value::String(kind) => Some(kind.as_str()),
+        // OpenAPI 3.1 type lists such as ["string", "null"]
+        serde_json::Value::Array(kinds) => kinds.iter().filter_map(|kind| kind.as_str()).find(|kind| *kind != "null"),
+        _ => None,
+    };
+    match kind {
+        Some("object") | None if schema.get("properties").is_some() => {
+            let fields = schema["properties"]
+                .as_object()
+                .into_iter()
+                .flatten()
+                .map(|(name, property)| (name.clone(), openapi_schema_example(root, property, depth + 1, refs)))
+                .collect();
+            serde_json::Value::Object(fields)
+        }
+        Some("object") => serde_json::json!({}),
+        Some("array") => serde_json::json!([openapi_schema_example(root, &schema["items"], depth + 1, refs)]),
+        Some("string") => serde_json::Value::String(
+            match schema["format"].as_str() {
+                Some("date-time") => "2024-01-01T00:00:00Z",
+                Some("date") => "2024-01-01",
+                Some("email") => "user@example.com",
+                Some("uuid") => "00000000-0000-0000-0000-000000000000",
+                Some("uri") | Some("url") => "https://example.com",
+                Some("binary") | Some("byte") => "",
+                _ => "string",
+            }
+            .to_string(),
+        ),
+        Some("integer") | Some("number") => serde_json::json!(0),
+        Some("boolean") => serde_json::json!(false),
+        _ => serde_json::Value::Null,
+    }
+}
+
+// First server URL with its {variables} set to their defaults
+fn openapi_server_url(root: &serde_json::Value, servers: &serde_json::Value) -> Option<String> {
+    let server = servers.as_array()?.first()?;
+    let mut url = server["url"].as_str()?.to_string();
+    for (name, variable) in server["variables"].as_object().into_iter().flatten() {
+        let value = value_text(&resolve_openapi_ref(root, variable)["default"]);
+        url = url.replace(&format!("{{{}}}", name), &value);
+    }
+    Some(url.trim_end_matches('/').to_string())
+}
+
+fn openapi_security_auth(
+    root: &serde_json::Value,
+    requirements: &serde_json::Value,
+    warnings: &mut Vec<String>,
+) -> Option<RequestAuth> {
+    let requirement = requirements.as_array()?.first()?.as_object()?;
+    let (name, _) = requirement.iter().next()?;
+    let scheme = resolve_openapi_ref(root, &root["components"]["securitySchemes"][name]);
+    match (scheme["type"].as_str()?, scheme["scheme"].as_str().map(str::to_ascii_lowercase).as_deref()) {
+        ("http", Some("basic")) => Some(RequestAuth::Basic {
+            username: "{{username}}".to_string(),
+            password: Some("{{password}}".to_string()),
+        }),
+        ("http", Some("bearer")) => Some(RequestAuth::Bearer { token: "{{token}}".to_string() }),
+        ("http", Some("digest")) => Some(RequestAuth::Digest {
+            username: "{{username}}".to_string(),
+            password: "{{password}}".to_string(),
+        }),
+        ("apiKey", _) if scheme["in"].as_str() != Some("cookie") => Some(RequestAuth::ApiKey {
+            key: scheme["name"].as_str().unwrap_or("X-API-Key").to_string(),
+            value: format!("{{{{{}}}}}", name),
+            location: if scheme["in"].as_str() == Some("query") { ApiKeyLocation::Query } else { ApiKeyLocation::Header },
+        }),
+        // OAuth 2 flows end in a bearer access token
+        ("oauth2", _) | ("openIdConnect", _) => Some(RequestAuth::Bearer { token: "{{accessToken}}".to_string() }),
+        (kind, _) => {
+            push_warning(warnings, format!("Security scheme '{}' ({}) is not supported", name, kind));
+            None
+        }
+    }
+}
+
+fn openapi_request_body(
+    root: &serde_json::Value,
+    request_body: &serde_json::Value,
+    headers: &mut HashMap<String, String>,
+    name: &str,
+    warnings: &mut Vec<String>,
+) -> Option<RequestBody> {
+    let content = resolve_openapi_ref(root, request_body)["content"].as_object()?;
+    let preferred = ["application/json", "application/x-www-form-urlencoded", "multipart/form-data"];
+    let (media_type, media) = preferred
+        .iter()
+        .find_map(|wanted| content.iter().find(|(media_type, _)| parse_content_type(media_type).0 == *wanted))
+        .or_else(|| content.iter().find(|(media_type, _)| parse_content_type(media_type).0.ends_with("+json")))
+        .or_else(|| content.iter().next())?;
+    let mime = parse_content_type(media_type).0;
+    let example = openapi_given_example(root, media)
+        .unwrap_or_else(|| openapi_schema_example(root, &media["schema"], 0, &mut Vec::new()));
+
+    if mime == "application/json" || mime.ends_with("+json") {
+        if mime != "application/json" {
+            headers.insert("Content-Type".to_string(), media_type.clone());
+        }
+        let content = serde_json::to_string_pretty(&example).unwrap_or_default();
+        return Some(RequestBody::Json { content });
+    }
+    let fields: Vec<(String, serde_json::Value)> = match &example {
+        serde_json::Value::Object(fields) => fields.iter().map(|(key, value)| (key.clone(), value.clone())).collect(),
+        _ => Vec::new(),
+    };
+    match mime.as_str() {
+        "application/x-www-form-urlencoded" => Some(RequestBody::Urlencoded {
+            fields: fields
+                .into_iter()
+                .map(|(key, value)| FormField { key, value: json_value_text(&value) })
+                .collect(),
+        }),
+        "multipart/form-data" => {
+            let properties = &resolve_openapi_ref(root, &media["schema"])["properties"];
+            Some(RequestBody::Multipart {
+                entries: fields
+                    .into_iter()
+                    .map(|(key, value)| {
+                        let property = resolve_openapi_ref(root, &properties[&key]);
+                        let is_file = matches!(property["format"].as_str(), Some("binary") | Some("base64"))
+                            || property.get("contentMediaType").is_some();
+                        if is_file {
+                            push_warning(warnings, format!("{}: file part '{}' is imported empty", name, key));
+                        }
+                        MultipartEntry {
+                            value: if is_file { String::new() } else { json_value_text(&value) },
+                            r#type: if is_file { "file" } else { "text" }.to_string(),
+                            filename: None,
+                            path: None,
+                            content_type: None,
+                            headers: None,
+                            key,
+                        }
+                    })
+                    .collect(),
+            })
+        }
+        "application/octet-stream" => {
+            push_warning(warnings, format!("{}: binary request body is imported empty", name));
+            Some(RequestBody::Binary { path: None, base64: None, content_type: Some(media_type.clone()) })
+        }
+        _ => Some(RequestBody::Text {
+            content: if example.is_null() { String::new() } else { json_value_text(&example) },
+            content_type: Some(media_type.clone()),
+        }),
+    }
+}
+
+// Parse an OpenAPI 3.x document (JSON or YAML) into one request template per operation
+#[tauri::command]
+pub fn import_openapi(content: String) -> Result<ImportedCollection, String> {
+    let root: serde_json::Value = match serde_json::from_str(&content) {
+        Ok(root) => root,
+        Err(_) => serde_yaml::from_str(&content).map_err(|e| format!("Invalid OpenAPI document: {}", e))?,
+    };
+    let version = root["openapi"].as_str().unwrap_or_default();
+    if !version.starts_with("3.") {
+        return Err(if root.get("swagger").is_some() {
+            "Swagger 2.0 documents are not supported, only OpenAPI 3".to_string()
+        } else {
+            "Not an OpenAPI 3 document: missing 'openapi' version".to_string()
+        });
+    }
+
+    let mut warnings = Vec::new();
+    let mut requests = Vec::new();
+    let root_server = openapi_server_url(&root, &root["servers"]);
+    if root_server.is_none() {
+        push_warning(&mut warnings, "The document has no servers, URLs are relative".to_string());
+    }
+
+    for (path, path_item) in root["paths"].as_object().into_iter().flatten() {
+        let path_item = resolve_openapi_ref(&root, path_item);
+        for method in ["get", "put", "post", "delete", "options", "head", "patch"] {
+            let Some(operation) = path_item.get(method) else { continue };
+            let server = openapi_server_url(&root, &operation["servers"])
+                .or_else(|| openapi_server_url(&root, &path_item["servers"]))
+                .or_else(|| root_server.clone())
+                .unwrap_or_default();
+
+            // Operation parameters override path-level ones with the same name and location
+            let mut parameters: Vec<&serde_json::Value> = Vec::new();
+            for parameter in path_item["parameters"].as_array().into_iter().flatten().chain(operation["parameters"].as_array().into_iter().flatten()) {
+                let parameter = resolve_openapi_ref(&root, parameter);
+                parameters.retain(|existing| existing["name"] != parameter["name"] || existing["in"] != parameter["in"]);
+                parameters.push(parameter);
+            }
+
+            let mut url_path = path.clone();
+            let mut query = Vec::new();
+            let mut headers = HashMap::new();
+            let mut cookies = Vec::new();
+            for parameter in parameters {
+                let name = parameter["name"].as_str().unwrap_or_default();
+                let example = openapi_given_example(&root, parameter).map(|value| match value {
+                    serde_json::Value::Array(items) => items.iter().map(json_value_text).collect::<Vec<_>>().join(","),
+                    other => json_value_text(&other),
+                });
+                let required = parameter["required"].as_bool().unwrap_or(false);
+                let value = example.clone().unwrap_or_else(|| format!("{{{{{}}}}}", name));
+                match parameter["in"].as_str() {
+                    Some("path") => {
+                        let value = if example.is_some() { rfc3986_encode(&value) } else { value };
+                        url_path = url_path.replace(&format!("{{{}}}", name), &value);
+                    }
+                    Some("query") if required || example.is_some() => {
+                        query.push(format!("{}={}", rfc3986_encode(name), if example.is_some() { rfc3986_encode(&value) } else { value }));
+                    }
+                    // Accept, Content-Type and Authorization are described elsewhere in OpenAPI
+                    Some("header")
+                        if (required || example.is_some())
+                            && !["accept", "content-type", "authorization"].contains(&name.to_ascii_lowercase().as_str()) =>
+                    {
+                        headers.insert(name.to_string(), value);
+                    }
+                    Some("cookie") if required || example.is_some() => cookies.push(format!("{}={}", name, value)),
+                    _ => {}
+                }
+            }
+            if !cookies.is_empty() {
+                headers.insert("Cookie".to_string(), cookies.join("; "));
+            }
+
+            let mut url = format!("{}{}", server, url_path);
+            if !query.is_empty() {
+                url.push('?');
+                url.push_str(&query.join("&"));
+            }
+
+            let title = operation["summary"]
+                .as_str()
+                .or_else(|| operation["operationId"].as_str())
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("{} {}", method.to_uppercase(), path));
+            let name = match operation["tags"].as_array().and_then(|tags| tags.first()?.as_str()) {
+                Some(tag) => format!("{} / {}", tag, title),
+                None => title,
+            };
+
+            let body = openapi_request_body(&root, &operation["requestBody"], &mut headers, &name, &mut warnings);
+            let security = if operation.get("security").is_some() { &operation["security"] } else { &root["security"] };
+            let auth = openapi_security_auth(&root, security, &mut warnings);
+
+            requests.push(CollectionRequest {
+                name: Some(name),
+                request: HttpRequest {
+                    url,
+                    method: method.to_uppercase(),
+                    headers,
+                    body,
+                    options: None,
+                    session: None,
+                    auth,
+                },
+                extract: None,
+                assertions: None,
+            });
+        }
+    }
+
+    Ok(ImportedCollection {
+        name: root["info"]["title"].as_str().unwrap_or("OpenAPI").to_string(),
+        requests,
+        variables: HashMap::new(),
+        warnings,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn curl_empty_header_removes_it_and_get_file_data_warns() {
+        let imported =
+            import_curl_command("curl -H 'X-A: 1' -H 'X-B: 2' -H 'x-a:' -H 'X-C;' -G -d @query.txt https://example.com".to_string())
+                .unwrap();
+
+        assert_eq!(imported.requests.len(), 1);
+        let request = &imported.requests[0];
+        assert_eq!(request.method, "GET");
+        assert!(request.body.is_none());
+        let mut headers: Vec<_> = request.headers.iter().map(|(name, value)| (name.as_str(), value.as_str())).collect();
+        headers.sort();
+        assert_eq!(headers, vec![("X-B", "2"), ("X-C", "")]);
+        assert_eq!(imported.warnings.len(), 1);
+        assert!(imported.warnings[0].contains("@query.txt"));
+    }
+
+    #[test]
+    fn imports_openapi_with_recursive_schemas() {
+        let document = serde_json::json!({
+            "openapi": "3.0.3",
+            "info": { "title": "Trees", "version": "1" },
+            "servers": [{ "url": "https://example.com" }],
+            "paths": {
+                "/nodes": {
+                    "post": {
+                        "parameters": [{ "$ref": "#/components/parameters/Depth" }],
+                        "requestBody": {
+                            "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Node" } } }
+                        }
+                    }
+                }
+            },
+            "components": {
+                "parameters": {
+                    "Depth": { "name": "depth", "in": "query", "schema": { "$ref": "#/components/parameters/Depth" } }
+                },
+                "schemas": {
+                    "Node": {
+                        "type": "object",
+                        "properties": {
+                            "value": { "type": "integer" },
+                            "parent": { "$ref": "#/components/schemas/Node" },
+                            "children": { "type": "array", "items": { "$ref": "#/components/schemas/Node" } }
+                        }
+                    }
+                }
+            }
+        });
+        let collection = import_openapi(document.to_string()).unwrap();
+
+        let value = serde_json::to_value(&collection.requests[0].request).unwrap();
+        let body: serde_json::Value = serde_json::from_str(value["body"]["content"].as_str().unwrap()).unwrap();
+        assert_eq!(body, serde_json::json!({ "value": 0, "parent": null, "children": [null] }));
+    }
+}
@@ -1,5 +1,4 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
 use base64::{Engine as _, engine::general_purpose};
 use dsa::{SigningKey, VerifyingKey, Components, Signature};
 use rand::rngs::OsRng;
@@ -26,146 +25,24 @@ use std::io::Cursor;
 use std::fs;
 use std::sync::Mutex;
 
+mod assertions;
+mod benchmark;
+mod capture_proxy;
+mod collection;
+mod export;
+mod grpc;
+mod history;
+mod http;
+mod import;
+mod mock_server;
+mod secrets;
+mod sockets;
+mod sse;
+mod websocket;
+
 // 全局变量存储要打开的文件路径
 static OPEN_FILE_PATH: Mutex<Option<String>> = Mutex::new(None);
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct HttpRequest {
-    url: String,
-    method: String,
-    headers: HashMap<String, String>,
-    body: Option<String>,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-struct MultipartEntry {
-    key: String,
-    value: String,
-    r#type: String, // "text" or "file"
-    filename: Option<String>,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-struct MultipartBody {
-    r#type: String,
-    entries: Vec<MultipartEntry>,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct HttpResponse {
-    status: u16,
-    headers: HashMap<String, String>,
-    body: String,
-    error: Option<String>,
-}
-
-#[tauri::command]
-async fn http_request(request: HttpRequest) -> Result<HttpResponse, String> {
-    let client = reqwest::Client::new();
-    
-    let mut req_builder = match request.method.as_str() {
-        "GET" => client.get(&request.url),
-        "POST" => client.post(&request.url),
-        "PUT" => client.put(&request.url),
-        "DELETE" => client.delete(&request.url),
-        "PATCH" => client.patch(&request.url),
-        "HEAD" => client.head(&request.url),
-        "OPTIONS" => client.request(reqwest::Method::OPTIONS, &request.url),
-        _ => return Err(format!("Unsupported HTTP method: {}", request.method)),
-    };
-
-    // 添加请求头
-    for (key, value) in request.headers {
-        // 如果是 multipart 请求，Content-Type 会被 reqwest 自动设置
-        if key.to_lowercase() != "content-type" || !request.body.as_ref().and_then(|b| serde_json::from_str::<MultipartBody>(b).ok()).map_or(false, |mb: MultipartBody| mb.r#type == "multipart") {
-            req_builder = req_builder.header(&key, &value);
-        }
-    }
-
-    // 添加请求体
-    if let Some(body) = request.body {
-        // 检查是否是 multipart 请求
-        if let Ok(multipart_body) = serde_json::from_str::<MultipartBody>(&body) {
-            if multipart_body.r#type == "multipart" {
-                // 构建 multipart form
-                let mut form = reqwest::multipart::Form::new();
-                
-                for entry in multipart_body.entries {
-                    if entry.r#type == "file" {
-                        // 解码 base64 文件
-                        if let Ok(file_bytes) = general_purpose::STANDARD.decode(&entry.value) {
-                            let part = if let Some(filename) = entry.filename.clone() {
-                                let filename_clone = filename.clone();
-                                reqwest::multipart::Part::bytes(file_bytes)
-                                    .file_name(filename)
-                                    .mime_str("application/octet-stream")
-                                    .unwrap_or_else(|_| {
-                                        // 如果 mime_str 失败，重新创建 Part（不使用 MIME 类型）
-                                        // 这不应该发生，但为了安全起见我们处理它
-                                        let file_bytes_copy = general_purpose::STANDARD.decode(&entry.value).unwrap();
-                                        reqwest::multipart::Part::bytes(file_bytes_copy)
-                                            .file_name(filename_clone)
-                                    })
-                            } else {
-                                reqwest::multipart::Part::bytes(file_bytes)
-                                    .mime_str("application/octet-stream")
-                                    .unwrap_or_else(|_| {
-                                        // 如果 mime_str 失败，重新创建 Part（不使用 MIME 类型）
-                                        let file_bytes_copy = general_purpose::STANDARD.decode(&entry.value).unwrap();
-                                        reqwest::multipart::Part::bytes(file_bytes_copy)
-                                    })
-                            };
-                            form = form.part(entry.key, part);
-                        }
-                    } else {
-                        // 文本字段
-                        form = form.text(entry.key, entry.value);
-                    }
-                }
-                
-                req_builder = req_builder.multipart(form);
-            } else {
-                req_builder = req_builder.body(body);
-            }
-        } else {
-            req_builder = req_builder.body(body);
-        }
-    }
-
-    match req_builder.send().await {
-        Ok(response) => {
-            let status = response.status().as_u16();
-            let mut headers = HashMap::new();
-            
-            // 获取响应头
-            for (key, value) in response.headers() {
-                if let Ok(value_str) = value.to_str() {
-                    headers.insert(key.to_string(), value_str.to_string());
-                }
-            }
-
-            // 获取响应体
-            let body = match response.text().await {
-                Ok(text) => text,
-                Err(e) => format!("Error reading response body: {}", e),
-            };
-
-            Ok(HttpResponse {
-                status,
-                headers,
-                body,
-                error: None,
-            })
-        }
-        Err(e) => Ok(HttpResponse {
-            status: 0,
-            headers: HashMap::new(),
-            body: String::new(),
-            error: Some(e.to_string()),
-        }),
-    }
-}
-
 // DSA 密钥对响应结构
 #[derive(Debug, Serialize, Deserialize)]
 pub struct DsaKeyPair {
@@ -1596,6 +1473,14 @@ pub fn run() {
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_deep_link::init())
+        .manage(http::HttpSessions::default())
+        .manage(http::HttpStreams::default())
+        .manage(history::HttpHistory::default())
+        .manage(mock_server::MockServers::default())
+        .manage(capture_proxy::CaptureProxies::default())
+        .manage(websocket::WebSockets::default())
+        .manage(sockets::Sockets::default())
+        .manage(secrets::SecretStore::default())
         .setup(|app| {
             // 处理命令行参数（用于右键菜单打开文件）
             let args: Vec<String> = std::env::args().collect();
@@ -1710,7 +1595,54 @@ pub fn run() {
         })
         .invoke_handler(tauri::generate_handler![
             greet, 
-            http_request, 
+            http::http_request, 
+            http::list_http_sessions,
+            http::get_http_session_cookies,
+            http::set_http_session_cookie,
+            http::delete_http_session_cookie,
+            http::clear_http_session_cookies,
+            http::remove_http_session,
+            http::http_request_stream,
+            http::cancel_http_request,
+            export::export_http_request,
+            import::import_curl_command,
+            import::import_har,
+            collection::run_http_collection,
+            import::import_postman_collection,
+            import::import_openapi,
+            history::query_http_history,
+            history::get_http_history_entry,
+            history::set_http_history_enabled,
+            history::rerun_http_history,
+            history::diff_http_history,
+            history::delete_http_history,
+            benchmark::run_http_benchmark,
+            mock_server::start_mock_server,
+            mock_server::stop_mock_server,
+            mock_server::list_mock_servers,
+            mock_server::get_mock_routes,
+            mock_server::set_mock_routes,
+            capture_proxy::start_capture_proxy,
+            capture_proxy::stop_capture_proxy,
+            capture_proxy::list_capture_proxies,
+            capture_proxy::list_captured_exchanges,
+            capture_proxy::clear_captured_exchanges,
+            capture_proxy::captured_exchange_to_request,
+            websocket::websocket_connect,
+            websocket::websocket_send,
+            websocket::websocket_close,
+            sse::sse_connect,
+            grpc::load_grpc_services,
+            grpc::grpc_call,
+            sockets::socket_connect,
+            sockets::socket_send,
+            sockets::socket_close,
+            secrets::secret_store_status,
+            secrets::unlock_secrets,
+            secrets::lock_secrets,
+            secrets::list_secrets,
+            secrets::set_secret,
+            secrets::delete_secret,
             generate_dsa_keypair, 
             dsa_sign, 
             dsa_verify,
@@ -0,0 +1,480 @@
+//! Mock HTTP server
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use base64::{Engine as _, engine::general_purpose};
+use serde::{Deserialize, Serialize};
+use crate::capture_proxy::is_hop_by_hop_header;
+use crate::collection::substitute_variables;
+use crate::http::{describe_request_error, header_map_to_hash_map, unix_now, unix_now_ms};
+
+// Response rule of a mock server. Paths may use :name or {name} segments and a trailing *.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MockRoute {
+    method: Option<String>, // any method when omitted or "*"
+    path: String,
+    status: Option<u16>, // 200 when omitted
+    #[serde(default)]
+    headers: HashMap<String, String>,
+    #[serde(default)]
+    repeated_headers: Vec<(String, String)>, // sent once per entry, e.g. several Set-Cookie lines
+    // Template with {{method}}, {{path}}, {{params.x}}, {{query.x}}, {{headers.x}}, {{body}} and {{timestamp}}
+    body: Option<String>,
+    body_base64: Option<String>, // sent as-is, also used for recorded bodies
+    delay_ms: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MockServerConfig {
+    host: Option<String>,       // 127.0.0.1 when omitted
+    port: Option<u16>,          // a free port when omitted or 0
+    allow_remote: Option<bool>, // required to listen on a host other than loopback
+    #[serde(default)]
+    routes: Vec<MockRoute>,
+    upstream: Option<String>, // base URL unmatched requests are forwarded to
+    record: Option<bool>,     // turn forwarded exchanges into routes, replayed from then on
+    upstream_timeout_ms: Option<u64>, // whole upstream exchange, 30 s when omitted
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MockServerInfo {
+    id: String,
+    url: String,
+    port: u16,
+    upstream: Option<String>,
+    record: bool,
+    routes: usize,
+}
+
+// Payload of the "mock-server-request" event
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MockRequestLog {
+    server_id: String,
+    timestamp: u64, // unix milliseconds
+    method: String,
+    path: String,
+    query: Option<String>,
+    headers: HashMap<String, String>,
+    body: String, // lossy UTF-8
+    source: String, // "route", "upstream", "recorded", "not_found" or "rejected" (unreadable or oversized body)
+    route: Option<usize>, // index of the matched or recorded route
+    status: u16,
+    duration_ms: f64,
+    error: Option<String>,
+}
+
+struct MockServerState {
+    id: String,
+    app: tauri::AppHandle,
+    routes: Mutex<Vec<MockRoute>>,
+    upstream: Option<String>,
+    record: bool,
+    client: reqwest::Client,
+}
+
+struct MockServer {
+    info: MockServerInfo,
+    state: std::sync::Arc<MockServerState>,
+    task: tokio::task::JoinHandle<()>,
+}
+
+// Running mock servers keyed by id
+#[derive(Default)]
+pub struct MockServers(Mutex<HashMap<String, MockServer>>);
+
+static MOCK_SERVER_COUNTER: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(1);
+
+const MOCK_BODY_LIMIT: usize = 32 * 1024 * 1024; // request and upstream response bodies
+const MOCK_UPSTREAM_TIMEOUT_MS: u64 = 30_000;
+const MOCK_UPSTREAM_CONNECT_TIMEOUT_MS: u64 = 10_000;
+
+type MockResponse = hyper::Response<http_body_util::Full<hyper::body::Bytes>>;
+
+// Local servers only listen on loopback unless remote access is asked for explicitly
+pub(crate) fn check_listen_host(host: &str, allow_remote: Option<bool>) -> Result<(), String> {
+    let loopback = host.eq_ignore_ascii_case("localhost")
+        || host
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .parse::<std::net::IpAddr>()
+            .is_ok_and(|ip| ip.is_loopback());
+    if loopback || allow_remote.unwrap_or(false) {
+        Ok(())
+    } else {
+        Err(format!(
+            "Listening on {} makes the server reachable from other machines; set allow_remote to confirm",
+            host
+        ))
+    }
+}
+
+// Read a request body up to the limit: 413 past it, 400 when the body cannot be read
+async fn read_limited_body(incoming: hyper::body::Incoming, limit: usize) -> Result<hyper::body::Bytes, (u16, String)> {
+    use http_body_util::BodyExt;
+    match http_body_util::Limited::new(incoming, limit).collect().await {
+        Ok(collected) => Ok(collected.to_bytes()),
+        Err(e) if e.is::<http_body_util::LengthLimitError>() => {
+            Err((413, format!("Request body is larger than {} bytes", limit)))
+        }
+        Err(e) => Err((400, format!("Failed to read the request body: {}", e))),
+    }
+}
+
+// Path parameters when the pattern matches the path
+fn match_mock_path(pattern: &str, path: &str) -> Option<HashMap<String, String>> {
+    let pattern: Vec<&str> = pattern.trim_matches('/').split('/').filter(|s| !s.is_empty()).collect();
+    let segments: Vec<&str> = path.trim_matches('/').split('/').filter(|s| !s.is_empty()).collect();
+    let mut params = HashMap::new();
+    for (i, part) in pattern.iter().enumerate() {
+        if *part == "*" || *part == "**" {
+            if i == pattern.len() - 1 {
+                params.insert("*".to_string(), segments.get(i..).unwrap_or_default().join("/"));
+                return Some(params);
+            }
+            segments.get(i)?;
+            continue;
+        }
+        let segment = segments.get(i)?;
+        let name = part
+            .strip_prefix(':')
+            .or_else(|| part.strip_prefix('{').and_then(|part| part.strip_suffix('}')));
+        match name {
+            Some(name) => {
+                let value = percent_encoding::percent_decode_str(segment).decode_utf8_lossy().into_owned();
+                params.insert(name.to_string(), value);
+            }
+            None if part == segment => {}
+            None => return None,
+        }
+    }
+    (pattern.len() == segments.len()).then_some(params)
+}
+
+fn mock_response(status: u16, headers: &HashMap<String, String>, body: Vec<u8>) -> MockResponse {
+    let mut response = hyper::Response::new(http_body_util::Full::new(hyper::body::Bytes::from(body)));
+    *response.status_mut() = hyper::StatusCode::from_u16(status).unwrap_or(hyper::StatusCode::OK);
+    append_mock_headers(&mut response, headers);
+    response
+}
+
+fn append_mock_headers<'a>(response: &mut MockResponse, headers: impl IntoIterator<Item = (&'a String, &'a String)>) {
+    for (name, value) in headers {
+        if let (Ok(name), Ok(value)) = (
+            hyper::header::HeaderName::from_bytes(name.as_bytes()),
+            hyper::header::HeaderValue::from_str(value),
+        ) {
+            response.headers_mut().append(name, value);
+        }
+    }
+}
+
+fn render_mock_route(
+    route: &MockRoute,
+    request: &hyper::http::request::Parts,
+    params: &HashMap<String, String>,
+    body: &[u8],
+) -> Result<Vec<u8>, String> {
+    if let Some(base64) = route.body_base64.as_deref().filter(|base64| !base64.is_empty()) {
+        return general_purpose::STANDARD
+            .decode(base64)
+            .map_err(|e| format!("Invalid body_base64 in route {}: {}", route.path, e));
+    }
+    let Some(template) = route.body.as_deref() else { return Ok(Vec::new()) };
+
+    let mut variables = HashMap::new();
+    variables.insert("method".to_string(), request.method.to_string());
+    variables.insert("path".to_string(), request.uri.path().to_string());
+    variables.insert("body".to_string(), String::from_utf8_lossy(body).into_owned());
+    variables.insert("timestamp".to_string(), unix_now().to_string());
+    for (name, value) in params {
+        variables.insert(format!("params.{}", name), value.clone());
+    }
+    if let Ok(url) = reqwest::Url::parse(&format!("http://localhost{}", request.uri)) {
+        for (name, value) in url.query_pairs() {
+            variables.insert(format!("query.{}", name), value.into_owned());
+        }
+    }
+    for (name, value) in &request.headers {
+        if let Ok(value) = value.to_str() {
+            variables.insert(format!("headers.{}", name.as_str()), value.to_string());
+        }
+    }
+    Ok(substitute_variables(template, &variables, &mut Vec::new()).into_bytes())
+}
+
+// Forward a request to the upstream server; returns the status, headers and body.
+// Headers are kept as a HeaderMap so repeated ones like Set-Cookie survive.
+async fn forward_mock_request(
+    state: &MockServerState,
+    upstream: &str,
+    request: &hyper::http::request::Parts,
+    body: Vec<u8>,
+) -> Result<(u16, reqwest::header::HeaderMap, Vec<u8>), String> {
+    let path_and_query = request.uri.path_and_query().map(|pq| pq.as_str()).unwrap_or("/");
+    let url = format!("{}{}", upstream.trim_end_matches('/'), path_and_query);
+    let mut forwarded = state.client.request(request.method.clone(), &url).body(body);
+    for (name, value) in &request.headers {
+        // The client sets Host and framing headers for the upstream connection itself
+        if !is_hop_by_hop_header(name.as_str()) && !matches!(name.as_str(), "host" | "content-length") {
+            forwarded = forwarded.header(name.as_str(), value.as_bytes());
+        }
+    }
+    let mut response = forwarded.send().await.map_err(|e| describe_request_error(&e))?;
+    let status = response.status().as_u16();
+    let headers = response
+        .headers()
+        .iter()
+        .filter(|(name, _)| !is_hop_by_hop_header(name.as_str()) && *name != reqwest::header::CONTENT_LENGTH)
+        .map(|(name, value)| (name.clone(), value.clone()))
+        .collect();
+    let mut body = Vec::new();
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| format!("Error reading upstream body: {}", describe_request_error(&e)))?
+    {
+        if body.len() + chunk.len() > MOCK_BODY_LIMIT {
+            return Err(format!("Upstream body is larger than {} bytes", MOCK_BODY_LIMIT));
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok((status, headers, body))
+}
+
+async fn handle_mock_request(
+    state: std::sync::Arc<MockServerState>,
+    request: hyper::Request<hyper::body::Incoming>,
+) -> Result<MockResponse, std::convert::Infallible> {
+    use tauri::Emitter;
+
+    let started = std::time::Instant::now();
+    let (parts, incoming) = request.into_parts();
+    let (body, rejected) = match read_limited_body(incoming, MOCK_BODY_LIMIT).await {
+        Ok(body) => (body.to_vec(), None),
+        Err(rejected) => (Vec::new(), Some(rejected)),
+    };
+    let path = parts.uri.path().to_string();
+
+    let matched = state.routes.lock().ok().and_then(|routes| {
+        routes.iter().enumerate().find_map(|(index, route)| {
+            let method_matches = route
+                .method
+                .as_deref()
+                .is_none_or(|method| method == "*" || method.eq_ignore_ascii_case(parts.method.as_str()));
+            let params = match_mock_path(&route.path, &path).filter(|_| method_matches)?;
+            Some((index, route.clone(), params))
+        })
+    });
+
+    let mut route_index = None;
+    let mut error = None;
+    let (source, response) = match (rejected, matched, state.upstream.as_deref()) {
+        (Some((status, message)), _, _) => {
+            error = Some(message.clone());
+            ("rejected", mock_response(status, &HashMap::new(), message.into_bytes()))
+        }
+        (None, Some((index, route, params)), _) => {
+            route_index = Some(index);
+            if let Some(delay) = route.delay_ms.filter(|delay| *delay > 0) {
+                tokio::time::sleep(std::time::Duration::from_millis(delay)).await;
+            }
+            let response = match render_mock_route(&route, &parts, &params, &body) {
+                Ok(rendered) => {
+                    let mut response = mock_response(route.status.unwrap_or(200), &route.headers, rendered);
+                    append_mock_headers(&mut response, route.repeated_headers.iter().map(|(name, value)| (name, value)));
+                    response
+                }
+                Err(e) => {
+                    error = Some(e.clone());
+                    mock_response(500, &HashMap::new(), e.into_bytes())
+                }
+            };
+            ("route", response)
+        }
+        (None, None, Some(upstream)) => match forward_mock_request(&state, upstream, &parts, body.clone()).await {
+            Ok((status, headers, upstream_body)) => {
+                let mut source = "upstream";
+                if state.record {
+                    // Single headers stay editable in the map, repeated ones keep every value
+                    let mut single = HashMap::new();
+                    let mut repeated = Vec::new();
+                    for name in headers.keys() {
+                        let values: Vec<&str> = headers.get_all(name).iter().filter_map(|value| value.to_str().ok()).collect();
+                        match values.as_slice() {
+                            [value] => {
+                                single.insert(name.to_string(), value.to_string());
+                            }
+                            _ => repeated.extend(values.iter().map(|value| (name.to_string(), value.to_string()))),
+                        }
+                    }
+                    // The body is kept verbatim; as a template, {{...}} in upstream content would be rendered
+                    let route = MockRoute {
+                        method: Some(parts.method.to_string()),
+                        path: path.clone(),
+                        status: Some(status),
+                        headers: single,
+                        repeated_headers: repeated,
+                        body: None,
+                        body_base64: Some(general_purpose::STANDARD.encode(&upstream_body)),
+                        delay_ms: None,
+                    };
+                    if let Ok(mut routes) = state.routes.lock() {
+                        routes.push(route);
+                        route_index = Some(routes.len() - 1);
+                        source = "recorded";
+                    }
+                }
+                let mut response = hyper::Response::new(http_body_util::Full::new(hyper::body::Bytes::from(upstream_body)));
+                *response.status_mut() = hyper::StatusCode::from_u16(status).unwrap_or(hyper::StatusCode::BAD_GATEWAY);
+                *response.headers_mut() = headers;
+                (source, response)
+            }
+            Err(e) => {
+                error = Some(e.clone());
+                ("upstream", mock_response(502, &HashMap::new(), format!("Upstream request failed: {}", e).into_bytes()))
+            }
+        },
+        (None, None, None) => ("not_found", mock_response(404, &HashMap::new(), b"No mock route matches this request".to_vec())),
+    };
+
+    let _ = state.app.emit(
+        "mock-server-request",
+        MockRequestLog {
+            server_id: state.id.clone(),
+            timestamp: unix_now_ms(),
+            method: parts.method.to_string(),
+            path,
+            query: parts.uri.query().map(str::to_string),
+            headers: header_map_to_hash_map(&parts.headers),
+            body: String::from_utf8_lossy(&body).into_owned(),
+            source: source.to_string(),
+            route: route_index,
+            status: response.status().as_u16(),
+            duration_ms: started.elapsed().as_secs_f64() * 1000.0,
+            error,
+        },
+    );
+    Ok(response)
+}
+
+// Accept connections until the task is aborted; aborting also drops the open connections
+async fn run_mock_server(listener: tokio::net::TcpListener, state: std::sync::Arc<MockServerState>) {
+    let mut connections = tokio::task::JoinSet::new();
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => {
+                let Ok((stream, _)) = accepted else { continue };
+                let state = state.clone();
+                connections.spawn(async move {
+                    let service = hyper::service::service_fn(move |request| handle_mock_request(state.clone(), request));
+                    let _ = hyper::server::conn::http1::Builder::new()
+                        .serve_connection(hyper_util::rt::TokioIo::new(stream), service)
+                        .await;
+                });
+            }
+            // Reap finished connections so the set does not grow
+            Some(_) = connections.join_next(), if !connections.is_empty() => {}
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn start_mock_server(
+    app: tauri::AppHandle,
+    servers: tauri::State<'_, MockServers>,
+    config: MockServerConfig,
+) -> Result<MockServerInfo, String> {
+    let host = config.host.filter(|host| !host.is_empty()).unwrap_or_else(|| "127.0.0.1".to_string());
+    check_listen_host(&host, config.allow_remote)?;
+    let listener = tokio::net::TcpListener::bind((host.as_str(), config.port.unwrap_or(0)))
+        .await
+        .map_err(|e| format!("Failed to listen on {}:{}: {}", host, config.port.unwrap_or(0), e))?;
+    let port = listener
+        .local_addr()
+        .map_err(|e| format!("Failed to read the listening address: {}", e))?
+        .port();
+    let upstream = config.upstream.filter(|upstream| !upstream.trim().is_empty());
+    if let Some(upstream) = &upstream {
+        reqwest::Url::parse(upstream).map_err(|e| format!("Invalid upstream URL: {}", e))?;
+    }
+
+    let id = format!("mock-{}", MOCK_SERVER_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed));
+    let record = config.record.unwrap_or(false) && upstream.is_some();
+    let info = MockServerInfo {
+        id: id.clone(),
+        url: format!("http://{}:{}", if host.contains(':') { format!("[{}]", host) } else { host.clone() }, port),
+        port,
+        upstream: upstream.clone(),
+        record,
+        routes: config.routes.len(),
+    };
+    // Upstream redirects and encodings are passed through untouched
+    // A stalled upstream fails the request with 502 instead of holding it open
+    let upstream_timeout = config.upstream_timeout_ms.filter(|ms| *ms > 0).unwrap_or(MOCK_UPSTREAM_TIMEOUT_MS);
+    let client = reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .connect_timeout(std::time::Duration::from_millis(upstream_timeout.min(MOCK_UPSTREAM_CONNECT_TIMEOUT_MS)))
+        .timeout(std::time::Duration::from_millis(upstream_timeout))
+        .build()
+        .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
+    let state = std::sync::Arc::new(MockServerState {
+        id: id.clone(),
+        app,
+        routes: Mutex::new(config.routes),
+        upstream,
+        record,
+        client,
+    });
+    let task = tokio::spawn(run_mock_server(listener, state.clone()));
+
+    servers
+        .0
+        .lock()
+        .map_err(|_| "Mock server state is unavailable".to_string())?
+        .insert(id, MockServer { info: info.clone(), state, task });
+    Ok(info)
+}
+
+#[tauri::command]
+pub fn stop_mock_server(servers: tauri::State<'_, MockServers>, id: String) -> Result<bool, String> {
+    let server = servers.0.lock().map_err(|_| "Mock server state is unavailable".to_string())?.remove(&id);
+    match server {
+        Some(server) => {
+            server.task.abort();
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+#[tauri::command]
+pub fn list_mock_servers(servers: tauri::State<'_, MockServers>) -> Result<Vec<MockServerInfo>, String> {
+    let servers = servers.0.lock().map_err(|_| "Mock server state is unavailable".to_string())?;
+    let mut list: Vec<MockServerInfo> = servers
+        .values()
+        .map(|server| MockServerInfo {
+            routes: server.state.routes.lock().map(|routes| routes.len()).unwrap_or_default(),
+            ..server.info.clone()
+        })
+        .collect();
+    list.sort_by_key(|server| server.port);
+    Ok(list)
+}
+
+// Current routes of a server, including the ones recorded from upstream traffic
+#[tauri::command]
+pub fn get_mock_routes(servers: tauri::State<'_, MockServers>, id: String) -> Result<Vec<MockRoute>, String> {
+    let servers = servers.0.lock().map_err(|_| "Mock server state is unavailable".to_string())?;
+    let server = servers.get(&id).ok_or_else(|| format!("Mock server not found: {}", id))?;
+    let routes = server.state.routes.lock().map_err(|_| "Mock server state is unavailable".to_string())?;
+    Ok(routes.clone())
+}
+
+// Replace the routes of a running server
+#[tauri::command]
+pub fn set_mock_routes(servers: tauri::State<'_, MockServers>, id: String, routes: Vec<MockRoute>) -> Result<(), String> {
+    let servers = servers.0.lock().map_err(|_| "Mock server state is unavailable".to_string())?;
+    let server = servers.get(&id).ok_or_else(|| format!("Mock server not found: {}", id))?;
+    *server.state.routes.lock().map_err(|_| "Mock server state is unavailable".to_string())? = routes;
+    Ok(())
+}
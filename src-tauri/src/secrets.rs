@@ -0,0 +1,358 @@
+//! Encrypted secret store
+
+use std::collections::HashMap;
+use std::fs;
+use std::sync::Mutex;
+use argon2::Argon2;
+use base64::{Engine as _, engine::general_purpose};
+use serde::{Deserialize, Serialize};
+use crate::collection::substitute_strings;
+use crate::http::{HttpResponse, rfc3986_encode};
+
+const SECRET_STORE_FILE: &str = "secrets.json";
+// Bound to every ciphertext so a store file cannot be replayed as another format
+const SECRET_STORE_AAD: &[u8] = b"kairoa-secrets-v1";
+
+// Argon2id parameters used to derive the store key from the passphrase
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct SecretKdf {
+    algorithm: String, // "argon2id"
+    memory_kib: u32,
+    iterations: u32,
+    parallelism: u32,
+    salt: String, // base64
+}
+
+// secrets.json: the whole name/value map sealed with XChaCha20-Poly1305
+#[derive(Debug, Serialize, Deserialize)]
+struct SecretStoreFile {
+    version: u32,
+    kdf: SecretKdf,
+    nonce: String,      // base64
+    ciphertext: String, // base64
+}
+
+struct UnlockedSecrets {
+    key: [u8; 32],
+    kdf: SecretKdf,
+    secrets: std::collections::BTreeMap<String, String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SecretStoreStatus {
+    exists: bool,
+    unlocked: bool,
+    count: Option<usize>, // only known while unlocked
+}
+
+// Secret values (as-is and percent-encoded) and the {{secret:NAME}} references they came from
+#[derive(Clone, Default)]
+pub(crate) struct SecretMask(Vec<(String, String)>);
+
+impl SecretMask {
+    pub(crate) fn apply(&self, text: &str) -> String {
+        self.0
+            .iter()
+            .fold(text.to_string(), |text, (value, reference)| text.replace(value, reference))
+    }
+
+    pub(crate) fn apply_headers(&self, headers: &mut HashMap<String, String>) {
+        for value in headers.values_mut() {
+            *value = self.apply(value);
+        }
+    }
+
+    pub(crate) fn apply_error(&self, error: &mut Option<String>) {
+        if let Some(error) = error {
+            *error = self.apply(error);
+        }
+    }
+
+    pub(crate) fn apply_response(&self, response: &mut HttpResponse) {
+        response.url = self.apply(&response.url);
+        self.apply_headers(&mut response.headers);
+        self.apply_error(&mut response.error);
+        for hop in &mut response.redirects {
+            hop.url = self.apply(&hop.url);
+            hop.location = self.apply(&hop.location);
+            self.apply_headers(&mut hop.headers);
+        }
+    }
+}
+
+// Secrets are only held in memory between unlock_secrets and lock_secrets
+#[derive(Default)]
+pub struct SecretStore(std::sync::Arc<Mutex<Option<UnlockedSecrets>>>);
+
+fn with_unlocked_secrets<T>(
+    store: &Mutex<Option<UnlockedSecrets>>,
+    f: impl FnOnce(&mut UnlockedSecrets) -> Result<T, String>,
+) -> Result<T, String> {
+    let mut store = store.lock().map_err(|_| "Secret store is unavailable".to_string())?;
+    match store.as_mut() {
+        Some(unlocked) => f(unlocked),
+        None => Err("The secret store is locked".to_string()),
+    }
+}
+
+impl SecretStore {
+    fn with_unlocked<T>(&self, f: impl FnOnce(&mut UnlockedSecrets) -> Result<T, String>) -> Result<T, String> {
+        with_unlocked_secrets(&self.0, f)
+    }
+
+    // Same, on the blocking pool, for changes that rewrite the store file
+    async fn with_unlocked_blocking<T: Send + 'static>(
+        &self,
+        f: impl FnOnce(&mut UnlockedSecrets) -> Result<T, String> + Send + 'static,
+    ) -> Result<T, String> {
+        let store = self.0.clone();
+        tokio::task::spawn_blocking(move || with_unlocked_secrets(&store, f))
+            .await
+            .map_err(|e| format!("Task execution failed: {}", e))?
+    }
+
+    // The values of the secrets referenced anywhere in a request, to put the references back
+    // wherever the request is echoed to the frontend (URLs, headers, error messages)
+    pub(crate) fn mask<T: Serialize>(&self, request: &T) -> SecretMask {
+        let Ok(store) = self.0.lock() else { return SecretMask::default() };
+        let Some(unlocked) = store.as_ref() else { return SecretMask::default() };
+        // Secret names need no JSON escaping, so the references survive serialization as written
+        let Ok(text) = serde_json::to_string(request) else { return SecretMask::default() };
+        let mut replacements: Vec<(String, String)> = text
+            .split("{{")
+            .skip(1)
+            .filter_map(|rest| rest.split_once("}}"))
+            .filter_map(|(name, _)| name.trim().strip_prefix("secret:"))
+            .filter_map(|name| unlocked.secrets.get_key_value(name.trim()))
+            .filter(|(_, value)| !value.is_empty())
+            .flat_map(|(name, value)| {
+                let reference = format!("{{{{secret:{}}}}}", name);
+                [(value.clone(), reference.clone()), (rfc3986_encode(value), reference)]
+            })
+            .collect();
+        // Longest first, so a secret containing another one is replaced whole
+        replacements.sort_by_key(|(value, _)| std::cmp::Reverse(value.len()));
+        replacements.dedup();
+        SecretMask(replacements)
+    }
+
+    // Replace {{secret:NAME}} references in every string of a request. Other {{variables}}
+    // are left untouched.
+    pub(crate) fn resolve<T: Serialize + serde::de::DeserializeOwned>(&self, request: T) -> Result<T, String> {
+        let store = self.0.lock().map_err(|_| "Secret store is unavailable".to_string())?;
+        let values: HashMap<String, String> = store
+            .iter()
+            .flat_map(|unlocked| unlocked.secrets.iter())
+            .map(|(name, value)| (format!("secret:{}", name), value.clone()))
+            .collect();
+        let mut unresolved = Vec::new();
+        let request = substitute_strings(request, &values, &mut unresolved)?;
+        let missing: Vec<&str> = unresolved
+            .iter()
+            .filter_map(|name| name.strip_prefix("secret:"))
+            .map(str::trim)
+            .collect();
+        match (missing.is_empty(), store.is_some()) {
+            (true, _) => Ok(request),
+            (false, false) => Err(format!(
+                "The secret store is locked; unlock it to use {}",
+                missing.join(", ")
+            )),
+            (false, true) => Err(format!("Unknown secret: {}", missing.join(", "))),
+        }
+    }
+}
+
+fn secret_store_path(app: &tauri::AppHandle) -> Result<std::path::PathBuf, String> {
+    use tauri::Manager;
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to resolve app data dir: {}", e))?;
+    Ok(dir.join(SECRET_STORE_FILE))
+}
+
+fn derive_secret_key(passphrase: &str, kdf: &SecretKdf) -> Result<[u8; 32], String> {
+    if kdf.algorithm != "argon2id" {
+        return Err(format!("Unsupported key derivation: {}", kdf.algorithm));
+    }
+    let salt = general_purpose::STANDARD
+        .decode(&kdf.salt)
+        .map_err(|e| format!("Invalid secret store salt: {}", e))?;
+    let params = argon2::Params::new(kdf.memory_kib, kdf.iterations, kdf.parallelism, Some(32))
+        .map_err(|e| format!("Invalid Argon2 parameters: {}", e))?;
+    let mut key = [0u8; 32];
+    Argon2::new(argon2::Algorithm::Argon2id, argon2::Version::V0x13, params)
+        .hash_password_into(passphrase.as_bytes(), &salt, &mut key)
+        .map_err(|e| format!("Failed to derive key: {}", e))?;
+    Ok(key)
+}
+
+fn write_secret_store(path: &std::path::Path, unlocked: &UnlockedSecrets) -> Result<(), String> {
+    use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+
+    let plaintext = serde_json::to_vec(&unlocked.secrets).map_err(|e| format!("Failed to serialize secrets: {}", e))?;
+    let nonce: [u8; 24] = rand::random();
+    let cipher = chacha20poly1305::XChaCha20Poly1305::new(&unlocked.key.into());
+    let ciphertext = cipher
+        .encrypt(&nonce.into(), Payload { msg: &plaintext, aad: SECRET_STORE_AAD })
+        .map_err(|_| "Failed to encrypt secrets".to_string())?;
+    let file = SecretStoreFile {
+        version: 1,
+        kdf: unlocked.kdf.clone(),
+        nonce: general_purpose::STANDARD.encode(nonce),
+        ciphertext: general_purpose::STANDARD.encode(ciphertext),
+    };
+    let content = serde_json::to_string_pretty(&file).map_err(|e| format!("Failed to serialize secrets: {}", e))?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("Failed to create secret store dir: {}", e))?;
+    }
+    let temp = path.with_extension("json.tmp");
+    fs::write(&temp, content).map_err(|e| format!("Failed to write secret store: {}", e))?;
+    fs::rename(&temp, path).map_err(|e| format!("Failed to write secret store: {}", e))
+}
+
+fn read_secret_store(path: &std::path::Path, passphrase: &str) -> Result<UnlockedSecrets, String> {
+    use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+
+    let content = fs::read_to_string(path).map_err(|e| format!("Failed to read secret store: {}", e))?;
+    let file: SecretStoreFile =
+        serde_json::from_str(&content).map_err(|e| format!("Invalid secret store file: {}", e))?;
+    if file.version != 1 {
+        return Err(format!("Unsupported secret store version: {}", file.version));
+    }
+    let nonce: [u8; 24] = general_purpose::STANDARD
+        .decode(&file.nonce)
+        .ok()
+        .and_then(|nonce| nonce.try_into().ok())
+        .ok_or_else(|| "Invalid secret store nonce".to_string())?;
+    let ciphertext = general_purpose::STANDARD
+        .decode(&file.ciphertext)
+        .map_err(|e| format!("Invalid secret store ciphertext: {}", e))?;
+
+    let key = derive_secret_key(passphrase, &file.kdf)?;
+    let cipher = chacha20poly1305::XChaCha20Poly1305::new(&key.into());
+    let plaintext = cipher
+        .decrypt(&nonce.into(), Payload { msg: &ciphertext, aad: SECRET_STORE_AAD })
+        .map_err(|_| "Wrong passphrase or damaged secret store".to_string())?;
+    let secrets = serde_json::from_slice(&plaintext).map_err(|e| format!("Invalid secret store content: {}", e))?;
+    Ok(UnlockedSecrets { key, kdf: file.kdf, secrets })
+}
+
+fn validate_secret_name(name: &str) -> Result<(), String> {
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.')) {
+        return Err(format!(
+            "Invalid secret name '{}': use letters, digits, '_', '-' and '.'",
+            name
+        ));
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn secret_store_status(app: tauri::AppHandle, store: tauri::State<'_, SecretStore>) -> Result<SecretStoreStatus, String> {
+    let exists = secret_store_path(&app)?.exists();
+    let store = store.0.lock().map_err(|_| "Secret store is unavailable".to_string())?;
+    Ok(SecretStoreStatus {
+        exists,
+        unlocked: store.is_some(),
+        count: store.as_ref().map(|unlocked| unlocked.secrets.len()),
+    })
+}
+
+// Unlock the store with its passphrase. The first unlock creates the store with that passphrase.
+#[tauri::command]
+pub async fn unlock_secrets(
+    app: tauri::AppHandle,
+    store: tauri::State<'_, SecretStore>,
+    passphrase: String,
+) -> Result<SecretStoreStatus, String> {
+    if passphrase.is_empty() {
+        return Err("Passphrase is required".to_string());
+    }
+    let path = secret_store_path(&app)?;
+    // Argon2 is deliberately slow, so the key is derived off the async runtime
+    let unlocked = tokio::task::spawn_blocking(move || open_secret_store(&path, &passphrase))
+        .await
+        .map_err(|e| format!("Task execution failed: {}", e))??;
+    let count = unlocked.secrets.len();
+    *store.0.lock().map_err(|_| "Secret store is unavailable".to_string())? = Some(unlocked);
+    Ok(SecretStoreStatus {
+        exists: true,
+        unlocked: true,
+        count: Some(count),
+    })
+}
+
+// Decrypt the store file, or create it when there is none yet
+fn open_secret_store(path: &std::path::Path, passphrase: &str) -> Result<UnlockedSecrets, String> {
+    if path.exists() {
+        read_secret_store(path, passphrase)
+    } else {
+        let params = argon2::Params::default();
+        let kdf = SecretKdf {
+            algorithm: "argon2id".to_string(),
+            memory_kib: params.m_cost(),
+            iterations: params.t_cost(),
+            parallelism: params.p_cost(),
+            salt: general_purpose::STANDARD.encode(rand::random::<[u8; 16]>()),
+        };
+        let unlocked = UnlockedSecrets {
+            key: derive_secret_key(passphrase, &kdf)?,
+            kdf,
+            secrets: Default::default(),
+        };
+        write_secret_store(path, &unlocked)?;
+        Ok(unlocked)
+    }
+}
+
+// Forget the key and every decrypted secret
+#[tauri::command]
+pub fn lock_secrets(store: tauri::State<'_, SecretStore>) -> Result<(), String> {
+    *store.0.lock().map_err(|_| "Secret store is unavailable".to_string())? = None;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn list_secrets(store: tauri::State<'_, SecretStore>) -> Result<Vec<String>, String> {
+    store.with_unlocked(|unlocked| Ok(unlocked.secrets.keys().cloned().collect()))
+}
+
+// Add or replace a secret, usable in requests as {{secret:NAME}}
+#[tauri::command]
+pub async fn set_secret(
+    app: tauri::AppHandle,
+    store: tauri::State<'_, SecretStore>,
+    name: String,
+    value: String,
+) -> Result<(), String> {
+    let name = name.trim().to_string();
+    validate_secret_name(&name)?;
+    let path = secret_store_path(&app)?;
+    store.with_unlocked_blocking(move |unlocked| {
+        let previous = unlocked.secrets.insert(name.clone(), value);
+        write_secret_store(&path, unlocked).inspect_err(|_| {
+            // Keep memory in line with the file when it could not be written
+            match previous {
+                Some(previous) => unlocked.secrets.insert(name, previous),
+                None => unlocked.secrets.remove(&name),
+            };
+        })
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn delete_secret(app: tauri::AppHandle, store: tauri::State<'_, SecretStore>, name: String) -> Result<bool, String> {
+    let path = secret_store_path(&app)?;
+    store
+        .with_unlocked_blocking(move |unlocked| {
+            let Some(previous) = unlocked.secrets.remove(&name) else { return Ok(false) };
+            write_secret_store(&path, unlocked).inspect_err(|_| {
+                unlocked.secrets.insert(name.clone(), previous);
+            })?;
+            Ok(true)
+        })
+        .await
+}
@@ -0,0 +1,409 @@
+//! Raw TCP/UDP socket client
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use base64::{Engine as _, engine::general_purpose};
+use serde::{Deserialize, Serialize};
+use crate::http::{build_http_tls_config, HTTP_STREAM_COUNTER, HttpRequestOptions, unix_now_ms};
+use crate::secrets::SecretStore;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SocketProtocol {
+    Tcp,
+    Udp,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SocketConnectRequest {
+    protocol: SocketProtocol,
+    host: String,
+    port: u16,
+    tls: Option<bool>,           // TCP only
+    server_name: Option<String>, // SNI and certificate name; the host by default
+    options: Option<HttpRequestOptions>, // timeout_ms (connect), insecure, CA and client certificate
+    connection_id: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SocketConnection {
+    connection_id: String,
+    protocol: SocketProtocol,
+    local_addr: String,
+    remote_addr: String,
+    tls_version: Option<String>,
+    cipher_suite: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TextLineEnding {
+    #[default]
+    None,
+    Lf,
+    Crlf,
+}
+
+// Outgoing payload, tagged by "type"
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SocketPayload {
+    Text {
+        text: String,
+        #[serde(default)]
+        line_ending: TextLineEnding, // appended to the text
+    },
+    Hex {
+        hex: String, // whitespace, ':' and ',' separators and 0x prefixes are ignored
+    },
+    Base64 {
+        base64: String,
+    },
+}
+
+// Payload of the "socket-data" event
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SocketData {
+    connection_id: String,
+    offset: u64, // bytes received before this chunk
+    size: usize,
+    data_base64: String,
+    text: Option<String>, // set when the chunk is valid UTF-8
+    hexdump: String,
+    timestamp: u64, // unix milliseconds
+}
+
+// Payload of the "socket-closed" event
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SocketClosed {
+    connection_id: String,
+    bytes_sent: u64,
+    bytes_received: u64,
+    by_peer: bool,
+    error: Option<String>,
+}
+
+enum SocketCommand {
+    Send(Vec<u8>),
+    Shutdown, // TCP half-close: stop writing, keep reading
+    Close,
+}
+
+// Open sockets, keyed by id, with the channel feeding their task
+#[derive(Default)]
+pub struct Sockets(Mutex<HashMap<String, tokio::sync::mpsc::UnboundedSender<SocketCommand>>>);
+
+// hexdump -C style lines, with offsets counted from the start of the connection
+fn hexdump(bytes: &[u8], offset: u64) -> String {
+    let mut lines = Vec::new();
+    for (index, row) in bytes.chunks(16).enumerate() {
+        let mut hex = String::new();
+        for (column, byte) in row.iter().enumerate() {
+            if column == 8 {
+                hex.push(' ');
+            }
+            hex.push_str(&format!("{:02x} ", byte));
+        }
+        let ascii: String = row
+            .iter()
+            .map(|&byte| if byte.is_ascii_graphic() || byte == b' ' { byte as char } else { '.' })
+            .collect();
+        lines.push(format!("{:08x}  {:<49} |{}|", offset + (index * 16) as u64, hex, ascii));
+    }
+    lines.join("\n")
+}
+
+fn socket_payload_bytes(payload: SocketPayload) -> Result<Vec<u8>, String> {
+    match payload {
+        SocketPayload::Text { text, line_ending } => {
+            let mut bytes = text.into_bytes();
+            match line_ending {
+                TextLineEnding::None => {}
+                TextLineEnding::Lf => bytes.push(b'\n'),
+                TextLineEnding::Crlf => bytes.extend_from_slice(b"\r\n"),
+            }
+            Ok(bytes)
+        }
+        SocketPayload::Hex { hex } => {
+            let digits: String = hex
+                .split(|c: char| c.is_whitespace() || c == ':' || c == ',')
+                .map(|token| token.strip_prefix("0x").or_else(|| token.strip_prefix("0X")).unwrap_or(token))
+                .collect();
+            hex::decode(&digits).map_err(|e| format!("Invalid hex payload: {}", e))
+        }
+        SocketPayload::Base64 { base64 } => general_purpose::STANDARD
+            .decode(base64.trim())
+            .map_err(|e| format!("Invalid base64 payload: {}", e)),
+    }
+}
+
+fn socket_sender(
+    sockets: &Sockets,
+    connection_id: &str,
+) -> Result<tokio::sync::mpsc::UnboundedSender<SocketCommand>, String> {
+    let sockets = sockets.0.lock().map_err(|_| "Socket state is unavailable".to_string())?;
+    sockets
+        .get(connection_id)
+        .cloned()
+        .ok_or_else(|| format!("Socket not found: {}", connection_id))
+}
+
+fn emit_socket_data(app: &tauri::AppHandle, closed: &mut SocketClosed, data: &[u8]) {
+    use tauri::Emitter;
+
+    let _ = app.emit(
+        "socket-data",
+        SocketData {
+            connection_id: closed.connection_id.clone(),
+            offset: closed.bytes_received,
+            size: data.len(),
+            data_base64: general_purpose::STANDARD.encode(data),
+            text: std::str::from_utf8(data).ok().map(str::to_string),
+            hexdump: hexdump(data, closed.bytes_received),
+            timestamp: unix_now_ms(),
+        },
+    );
+    closed.bytes_received += data.len() as u64;
+}
+
+async fn run_stream_socket<T>(
+    app: &tauri::AppHandle,
+    stream: T,
+    mut commands: tokio::sync::mpsc::UnboundedReceiver<SocketCommand>,
+    closed: &mut SocketClosed,
+) where
+    T: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let (mut reader, mut writer) = tokio::io::split(stream);
+    let mut buffer = vec![0u8; 16 * 1024];
+    let mut writing = true;
+    loop {
+        tokio::select! {
+            read = reader.read(&mut buffer) => match read {
+                Ok(0) => {
+                    closed.by_peer = true;
+                    break;
+                }
+                Ok(n) => emit_socket_data(app, closed, &buffer[..n]),
+                Err(e) => {
+                    closed.error = Some(format!("Failed to read from socket: {}", e));
+                    break;
+                }
+            },
+            // After a half-close only Send is ignored; Close still ends the connection
+            command = commands.recv() => match command {
+                Some(SocketCommand::Send(_)) if !writing => {}
+                Some(SocketCommand::Send(data)) => {
+                    if let Err(e) = writer.write_all(&data).await {
+                        closed.error = Some(format!("Failed to write to socket: {}", e));
+                        break;
+                    }
+                    closed.bytes_sent += data.len() as u64;
+                }
+                Some(SocketCommand::Shutdown) => {
+                    if writing {
+                        writing = false;
+                        let _ = writer.shutdown().await;
+                    }
+                }
+                Some(SocketCommand::Close) | None => {
+                    let _ = writer.shutdown().await;
+                    break;
+                }
+            },
+        }
+    }
+}
+
+async fn run_udp_socket(
+    app: &tauri::AppHandle,
+    socket: tokio::net::UdpSocket,
+    mut commands: tokio::sync::mpsc::UnboundedReceiver<SocketCommand>,
+    closed: &mut SocketClosed,
+) {
+    let mut buffer = vec![0u8; 65536];
+    loop {
+        tokio::select! {
+            received = socket.recv(&mut buffer) => match received {
+                Ok(n) => emit_socket_data(app, closed, &buffer[..n]),
+                // ICMP port unreachable from an earlier datagram; the peer may come up later
+                Err(e) if e.kind() == std::io::ErrorKind::ConnectionRefused => {}
+                Err(e) => {
+                    closed.error = Some(format!("Failed to receive datagram: {}", e));
+                    break;
+                }
+            },
+            command = commands.recv() => match command {
+                Some(SocketCommand::Send(data)) => match socket.send(&data).await {
+                    Ok(n) => closed.bytes_sent += n as u64,
+                    Err(e) => {
+                        closed.error = Some(format!("Failed to send datagram: {}", e));
+                        break;
+                    }
+                },
+                Some(SocketCommand::Shutdown) => {}
+                Some(SocketCommand::Close) | None => break,
+            },
+        }
+    }
+}
+
+// Open a TCP (optionally TLS) or UDP socket. Received data arrives as "socket-data" events
+// and the end of the connection as "socket-closed".
+#[tauri::command]
+pub async fn socket_connect(
+    app: tauri::AppHandle,
+    sockets: tauri::State<'_, Sockets>,
+    request: SocketConnectRequest,
+) -> Result<SocketConnection, String> {
+    let host = request.host.trim().trim_start_matches('[').trim_end_matches(']').to_string();
+    if host.is_empty() {
+        return Err("Host is required".to_string());
+    }
+    if request.port == 0 {
+        return Err("Invalid port".to_string());
+    }
+    let options = request.options.unwrap_or_default();
+    let connect_timeout = std::time::Duration::from_millis(options.timeout_ms.filter(|ms| *ms > 0).unwrap_or(10_000));
+    let remote = tokio::time::timeout(connect_timeout, tokio::net::lookup_host((host.as_str(), request.port)))
+        .await
+        .map_err(|_| format!("Timed out resolving {}", host))?
+        .map_err(|e| format!("Failed to resolve {}: {}", host, e))?
+        .next()
+        .ok_or_else(|| format!("No address found for {}", host))?;
+
+    enum Opened {
+        Tcp(tokio::net::TcpStream),
+        Tls(Box<tokio_rustls::client::TlsStream<tokio::net::TcpStream>>),
+        Udp(tokio::net::UdpSocket),
+    }
+    let mut connection = SocketConnection {
+        connection_id: String::new(),
+        protocol: request.protocol,
+        local_addr: String::new(),
+        remote_addr: remote.to_string(),
+        tls_version: None,
+        cipher_suite: None,
+    };
+    let opened = match request.protocol {
+        SocketProtocol::Tcp => {
+            let tcp = tokio::time::timeout(connect_timeout, tokio::net::TcpStream::connect(remote))
+                .await
+                .map_err(|_| format!("Timed out connecting to {}", remote))?
+                .map_err(|e| format!("Failed to connect to {}: {}", remote, e))?;
+            let _ = tcp.set_nodelay(true);
+            connection.local_addr = tcp.local_addr().map(|addr| addr.to_string()).unwrap_or_default();
+            if request.tls.unwrap_or(false) {
+                let mut tls = build_http_tls_config(&options)?;
+                tls.alpn_protocols.clear();
+                let name = request.server_name.filter(|name| !name.trim().is_empty()).unwrap_or(host.clone());
+                let server_name = rustls::pki_types::ServerName::try_from(name.trim().to_string())
+                    .map_err(|e| format!("Invalid server name {}: {}", name, e))?;
+                let stream = tokio::time::timeout(
+                    connect_timeout,
+                    tokio_rustls::TlsConnector::from(std::sync::Arc::new(tls)).connect(server_name, tcp),
+                )
+                .await
+                .map_err(|_| format!("Timed out during the TLS handshake with {}", remote))?
+                .map_err(|e| format!("TLS handshake failed: {}", e))?;
+                let session = stream.get_ref().1;
+                connection.tls_version = session.protocol_version().map(|version| match version {
+                    rustls::ProtocolVersion::TLSv1_2 => "TLS 1.2".to_string(),
+                    rustls::ProtocolVersion::TLSv1_3 => "TLS 1.3".to_string(),
+                    other => format!("{:?}", other),
+                });
+                connection.cipher_suite = session
+                    .negotiated_cipher_suite()
+                    .map(|suite| format!("{:?}", suite.suite()));
+                Opened::Tls(Box::new(stream))
+            } else {
+                Opened::Tcp(tcp)
+            }
+        }
+        SocketProtocol::Udp => {
+            let local: std::net::SocketAddr = if remote.is_ipv4() {
+                (std::net::Ipv4Addr::UNSPECIFIED, 0).into()
+            } else {
+                (std::net::Ipv6Addr::UNSPECIFIED, 0).into()
+            };
+            let socket = tokio::net::UdpSocket::bind(local)
+                .await
+                .map_err(|e| format!("Failed to open UDP socket: {}", e))?;
+            // Connecting only fixes the peer: datagrams from other addresses are dropped
+            socket
+                .connect(remote)
+                .await
+                .map_err(|e| format!("Failed to connect to {}: {}", remote, e))?;
+            connection.local_addr = socket.local_addr().map(|addr| addr.to_string()).unwrap_or_default();
+            Opened::Udp(socket)
+        }
+    };
+
+    let prefix = match request.protocol {
+        SocketProtocol::Tcp => "tcp",
+        SocketProtocol::Udp => "udp",
+    };
+    connection.connection_id = request.connection_id.filter(|id| !id.is_empty()).unwrap_or_else(|| {
+        format!("{}-{}", prefix, HTTP_STREAM_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed))
+    });
+    let (sender, commands) = tokio::sync::mpsc::unbounded_channel();
+    {
+        let mut active = sockets.0.lock().map_err(|_| "Socket state is unavailable".to_string())?;
+        if active.contains_key(&connection.connection_id) {
+            return Err(format!("Connection id already in use: {}", connection.connection_id));
+        }
+        active.insert(connection.connection_id.clone(), sender);
+    }
+
+    let id = connection.connection_id.clone();
+    tokio::spawn(async move {
+        use tauri::{Emitter, Manager};
+
+        let mut closed = SocketClosed {
+            connection_id: id.clone(),
+            bytes_sent: 0,
+            bytes_received: 0,
+            by_peer: false,
+            error: None,
+        };
+        match opened {
+            Opened::Tcp(stream) => run_stream_socket(&app, stream, commands, &mut closed).await,
+            Opened::Tls(stream) => run_stream_socket(&app, stream, commands, &mut closed).await,
+            Opened::Udp(socket) => run_udp_socket(&app, socket, commands, &mut closed).await,
+        }
+        if let Ok(mut active) = app.state::<Sockets>().0.lock() {
+            active.remove(&id);
+        }
+        let _ = app.emit("socket-closed", closed);
+    });
+
+    Ok(connection)
+}
+
+// Queue a payload on an open socket and return its size in bytes
+#[tauri::command]
+pub fn socket_send(
+    sockets: tauri::State<'_, Sockets>,
+    secrets: tauri::State<'_, SecretStore>,
+    connection_id: String,
+    payload: SocketPayload,
+) -> Result<usize, String> {
+    let data = socket_payload_bytes(secrets.resolve(payload)?)?;
+    let size = data.len();
+    socket_sender(&sockets, &connection_id)?
+        .send(SocketCommand::Send(data))
+        .map_err(|_| "The socket is closed".to_string())?;
+    Ok(size)
+}
+
+// Close a socket. With half_close a TCP connection only stops sending and keeps
+// receiving until the peer closes it.
+#[tauri::command]
+pub fn socket_close(sockets: tauri::State<'_, Sockets>, connection_id: String, half_close: Option<bool>) -> Result<(), String> {
+    let command = if half_close.unwrap_or(false) { SocketCommand::Shutdown } else { SocketCommand::Close };
+    socket_sender(&sockets, &connection_id)?
+        .send(command)
+        .map_err(|_| "The socket is closed".to_string())
+}
@@ -0,0 +1,401 @@
+//! Server-Sent Events client
+
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use crate::http::{build_http_request, DEFAULT_HTTP_SESSION, describe_request_error, header_map_to_hash_map, HTTP_STREAM_COUNTER, HttpRequest, HttpSessions, HttpStreams, parse_content_type, send_http_request, unix_now_ms};
+use crate::secrets::{SecretMask, SecretStore};
+
+// Reconnection delay used until the server sends a retry field
+const SSE_DEFAULT_RETRY_MS: u64 = 3000;
+
+// Payload of the "sse-open" event, sent for every (re)connection
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SseOpen {
+    request_id: String,
+    status: u16,
+    headers: HashMap<String, String>,
+    url: String,
+    attempt: u32, // 0 for the first connection
+}
+
+// Payload of the "sse-event" event
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SseEvent {
+    request_id: String,
+    event: String, // "message" unless the server named it
+    data: String,
+    id: Option<String>, // last event id in effect for this event
+    timestamp: u64,     // unix milliseconds
+}
+
+// Payload of the "sse-reconnect" event
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SseReconnect {
+    request_id: String,
+    attempt: u32,
+    delay_ms: u64,
+    last_event_id: Option<String>,
+    reason: String,
+}
+
+// Payload of the "sse-end" event
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SseEnd {
+    request_id: String,
+    events: u64,
+    reconnects: u32,
+    last_event_id: Option<String>,
+    cancelled: bool,
+    error: Option<String>,
+}
+
+// Incremental parser for the text/event-stream format
+#[derive(Default)]
+struct SseParser {
+    line: Vec<u8>,
+    skip_lf: bool, // the previous line ended with CR, so a leading LF belongs to it
+    started: bool, // past the optional byte order mark
+    event: String,
+    data: String,
+    last_event_id: Option<String>,   // committed when an event is dispatched
+    pending_id: Option<Option<String>>, // id field of the event being received, not yet committed
+    retry_ms: Option<u64>,
+}
+
+impl SseParser {
+    // Feed a chunk and return the (event type, data, last event id) of the events it completes
+    fn feed(&mut self, chunk: &[u8]) -> Vec<(String, String, Option<String>)> {
+        let mut events = Vec::new();
+        for &byte in chunk {
+            if self.skip_lf {
+                self.skip_lf = false;
+                if byte == b'\n' {
+                    continue;
+                }
+            }
+            match byte {
+                b'\r' | b'\n' => {
+                    self.skip_lf = byte == b'\r';
+                    let line = std::mem::take(&mut self.line);
+                    if let Some(event) = self.process_line(&line) {
+                        events.push(event);
+                    }
+                }
+                _ => self.line.push(byte),
+            }
+        }
+        events
+    }
+
+    fn process_line(&mut self, line: &[u8]) -> Option<(String, String, Option<String>)> {
+        let mut line = String::from_utf8_lossy(line).into_owned();
+        if !self.started {
+            self.started = true;
+            if let Some(rest) = line.strip_prefix('\u{feff}') {
+                line = rest.to_string();
+            }
+        }
+        if line.is_empty() {
+            // A blank line dispatches the event collected so far. Its id takes effect even
+            // when there is no data to deliver.
+            if let Some(id) = self.pending_id.take() {
+                self.last_event_id = id;
+            }
+            let event = std::mem::take(&mut self.event);
+            let mut data = std::mem::take(&mut self.data);
+            if data.is_empty() {
+                return None;
+            }
+            data.pop();
+            let event = if event.is_empty() { "message".to_string() } else { event };
+            return Some((event, data, self.last_event_id.clone()));
+        }
+        if line.starts_with(':') {
+            return None;
+        }
+        let (field, value) = match line.split_once(':') {
+            Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+            None => (line.as_str(), ""),
+        };
+        match field {
+            "event" => self.event = value.to_string(),
+            "data" => {
+                self.data.push_str(value);
+                self.data.push('\n');
+            }
+            "id" if !value.contains('\0') => self.pending_id = Some(Some(value.to_string()).filter(|id| !id.is_empty())),
+            "retry" if !value.is_empty() && value.bytes().all(|b| b.is_ascii_digit()) => {
+                self.retry_ms = value.parse().ok();
+            }
+            _ => {}
+        }
+        None
+    }
+
+    // Drop a partially received event when the connection is lost
+    fn reset(&mut self) {
+        self.line.clear();
+        self.skip_lf = false;
+        self.started = false;
+        self.event.clear();
+        self.data.clear();
+        self.pending_id = None;
+    }
+}
+
+// How a single SSE connection ended
+enum SseAttempt {
+    Cancelled,
+    Closed(String), // reconnect after the retry delay
+    Failed(String), // give up
+}
+
+// Subscribe to an event stream. Events arrive as "sse-event", reconnections are announced with
+// "sse-reconnect" and "sse-end" follows once the stream is cancelled or gives up. Cancel with
+// cancel_http_request.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn sse_connect(
+    app: tauri::AppHandle,
+    sessions: tauri::State<'_, HttpSessions>,
+    streams: tauri::State<'_, HttpStreams>,
+    secrets: tauri::State<'_, SecretStore>,
+    request: HttpRequest,
+    request_id: Option<String>,
+    last_event_id: Option<String>,
+    max_reconnects: Option<u32>, // unlimited when omitted
+) -> Result<String, String> {
+    let mask = secrets.mask(&request);
+    let request = secrets.resolve(request)?;
+    let mut options = request.options.clone().unwrap_or_default();
+    // The stream stays open indefinitely, so timeout_ms only limits waiting for the response headers
+    let connect_timeout = options.timeout_ms.take().filter(|ms| *ms > 0);
+    options.capture_redirects = None;
+    let session = request.session.as_deref().unwrap_or(DEFAULT_HTTP_SESSION);
+    let client = sessions.client(session, &options)?;
+    // Fail early on a request that cannot be built
+    build_http_request(&client, request.clone())?
+        .build()
+        .map_err(|e| describe_request_error(&e))?;
+
+    let request_id = request_id
+        .filter(|id| !id.is_empty())
+        .unwrap_or_else(|| format!("sse-{}", HTTP_STREAM_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed)));
+    let cancel = std::sync::Arc::new(tokio::sync::Notify::new());
+    {
+        let mut active = streams.0.lock().map_err(|_| "HTTP stream state is unavailable".to_string())?;
+        if active.contains_key(&request_id) {
+            return Err(format!("Request id already in use: {}", request_id));
+        }
+        active.insert(request_id.clone(), cancel.clone());
+    }
+
+    let id = request_id.clone();
+    tokio::spawn(async move {
+        use tauri::{Emitter, Manager};
+
+        let mut parser = SseParser {
+            last_event_id: last_event_id.filter(|id| !id.is_empty()),
+            ..Default::default()
+        };
+        let mut end = SseEnd {
+            request_id: id.clone(),
+            events: 0,
+            reconnects: 0,
+            last_event_id: None,
+            cancelled: false,
+            error: None,
+        };
+        loop {
+            let attempt = run_sse_attempt(
+                &app,
+                &client,
+                request.clone(),
+                &mask,
+                connect_timeout,
+                &id,
+                end.reconnects,
+                &mut parser,
+                &mut end.events,
+                &cancel,
+            )
+            .await;
+            let reason = match attempt {
+                SseAttempt::Cancelled => {
+                    end.cancelled = true;
+                    break;
+                }
+                SseAttempt::Failed(error) => {
+                    end.error = Some(mask.apply(&error));
+                    break;
+                }
+                SseAttempt::Closed(reason) => mask.apply(&reason),
+            };
+            if max_reconnects.is_some_and(|max| end.reconnects >= max) {
+                end.error = Some(reason);
+                break;
+            }
+            end.reconnects += 1;
+            let delay_ms = parser.retry_ms.unwrap_or(SSE_DEFAULT_RETRY_MS);
+            let _ = app.emit(
+                "sse-reconnect",
+                SseReconnect {
+                    request_id: id.clone(),
+                    attempt: end.reconnects,
+                    delay_ms,
+                    last_event_id: parser.last_event_id.clone(),
+                    reason,
+                },
+            );
+            tokio::select! {
+                _ = tokio::time::sleep(std::time::Duration::from_millis(delay_ms)) => {}
+                _ = cancel.notified() => {
+                    end.cancelled = true;
+                    break;
+                }
+            }
+        }
+
+        end.last_event_id = parser.last_event_id;
+        if let Ok(mut active) = app.state::<HttpStreams>().0.lock() {
+            active.remove(&id);
+        }
+        let _ = app.emit("sse-end", end);
+    });
+
+    Ok(request_id)
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn run_sse_attempt(
+    app: &tauri::AppHandle,
+    client: &reqwest::Client,
+    request: HttpRequest,
+    mask: &SecretMask,
+    connect_timeout: Option<u64>,
+    request_id: &str,
+    attempt: u32,
+    parser: &mut SseParser,
+    events: &mut u64,
+    cancel: &tokio::sync::Notify,
+) -> SseAttempt {
+    use tauri::Emitter;
+
+    let auth = request.auth.clone();
+    let built = build_http_request(client, request)
+        .and_then(|builder| builder.build().map_err(|e| describe_request_error(&e)));
+    let mut built = match built {
+        Ok(built) => built,
+        Err(e) => return SseAttempt::Failed(e),
+    };
+    let headers = built.headers_mut();
+    if !headers.contains_key(reqwest::header::ACCEPT) {
+        headers.insert(reqwest::header::ACCEPT, reqwest::header::HeaderValue::from_static("text/event-stream"));
+    }
+    headers.insert(reqwest::header::CACHE_CONTROL, reqwest::header::HeaderValue::from_static("no-cache"));
+    if let Some(last_event_id) = &parser.last_event_id {
+        match reqwest::header::HeaderValue::from_str(last_event_id) {
+            Ok(value) => {
+                headers.insert("Last-Event-ID", value);
+            }
+            Err(e) => return SseAttempt::Failed(format!("Invalid Last-Event-ID: {}", e)),
+        }
+    }
+
+    let sending = send_http_request(client, built, auth.as_ref());
+    let sent = tokio::select! {
+        result = async {
+            match connect_timeout {
+                Some(ms) => tokio::time::timeout(std::time::Duration::from_millis(ms), sending)
+                    .await
+                    .unwrap_or_else(|_| Err(format!("No response within {} ms", ms))),
+                None => sending.await,
+            }
+        } => result,
+        _ = cancel.notified() => return SseAttempt::Cancelled,
+    };
+    let mut response = match sent {
+        Ok(response) => response,
+        // Network errors are retried like a dropped stream
+        Err(e) => return SseAttempt::Closed(e),
+    };
+
+    let status = response.status();
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(|header| parse_content_type(header).0);
+    let mut headers = header_map_to_hash_map(response.headers());
+    mask.apply_headers(&mut headers);
+    let _ = app.emit(
+        "sse-open",
+        SseOpen {
+            request_id: request_id.to_string(),
+            status: status.as_u16(),
+            headers,
+            url: mask.apply(response.url().as_str()),
+            attempt,
+        },
+    );
+    // 204 is the server's way of telling the client to stop reconnecting
+    if status == reqwest::StatusCode::NO_CONTENT {
+        return SseAttempt::Failed("Server ended the stream with 204 No Content".to_string());
+    }
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        let detail = if body.is_empty() { String::new() } else { format!(": {}", body) };
+        return SseAttempt::Failed(format!("HTTP {}{}", status, detail));
+    }
+    if content_type.as_deref() != Some("text/event-stream") {
+        return SseAttempt::Failed(format!(
+            "Expected text/event-stream, got {}",
+            content_type.as_deref().unwrap_or("no content type")
+        ));
+    }
+
+    parser.reset();
+    loop {
+        let chunk = tokio::select! {
+            chunk = response.chunk() => chunk,
+            _ = cancel.notified() => return SseAttempt::Cancelled,
+        };
+        let chunk = match chunk {
+            Ok(Some(chunk)) => chunk,
+            Ok(None) => return SseAttempt::Closed("Stream closed by the server".to_string()),
+            Err(e) => return SseAttempt::Closed(describe_request_error(&e)),
+        };
+        for (event, data, id) in parser.feed(&chunk) {
+            *events += 1;
+            let _ = app.emit(
+                "sse-event",
+                SseEvent {
+                    request_id: request_id.to_string(),
+                    event,
+                    data,
+                    id,
+                    timestamp: unix_now_ms(),
+                },
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn commits_event_id_on_dispatch_only() {
+        let mut parser = SseParser::default();
+        let events = parser.feed(b"id: 1\ndata: one\n\nid: 2\ndata: tw");
+        assert_eq!(events, vec![("message".to_string(), "one".to_string(), Some("1".to_string()))]);
+        // Connection lost before the blank line: event 2 was never delivered
+        parser.reset();
+        assert_eq!(parser.last_event_id.as_deref(), Some("1"));
+
+        // An id without data still takes effect once dispatched
+        assert!(parser.feed(b"id: 3\n\n").is_empty());
+        assert_eq!(parser.last_event_id.as_deref(), Some("3"));
+    }
+}
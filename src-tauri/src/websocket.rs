@@ -0,0 +1,296 @@
+//! WebSocket client
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use base64::{Engine as _, engine::general_purpose};
+use serde::{Deserialize, Serialize};
+use crate::http::{build_http_tls_config, header_map_to_hash_map, HTTP_STREAM_COUNTER, HttpRequestOptions, unix_now_ms};
+use crate::secrets::SecretStore;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WebSocketConnectRequest {
+    url: String, // ws:// or wss://
+    #[serde(default)]
+    headers: HashMap<String, String>,
+    #[serde(default)]
+    protocols: Vec<String>, // offered subprotocols, in order of preference
+    options: Option<HttpRequestOptions>, // timeout_ms (handshake), insecure, CA and client certificate
+    connection_id: Option<String>,
+}
+
+// Handshake result returned by websocket_connect
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WebSocketConnection {
+    connection_id: String,
+    url: String,
+    status: u16,
+    protocol: Option<String>, // subprotocol selected by the server
+    request_headers: HashMap<String, String>,
+    response_headers: HashMap<String, String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum WebSocketOutgoing {
+    Text { text: String },
+    Binary { base64: String },
+    Ping { base64: Option<String> },
+    Pong { base64: Option<String> },
+}
+
+// Payload of the "websocket-message" event
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WebSocketMessage {
+    connection_id: String,
+    kind: String, // "text", "binary", "ping" or "pong"
+    text: Option<String>,
+    data_base64: String,
+    size: usize,
+    timestamp: u64, // unix milliseconds
+}
+
+// Payload of the "websocket-closed" event
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WebSocketClosed {
+    connection_id: String,
+    code: Option<u16>,
+    reason: Option<String>,
+    error: Option<String>,
+}
+
+// Open connections, keyed by id, with the channel feeding their writer task
+#[derive(Default)]
+pub struct WebSockets(Mutex<HashMap<String, tokio::sync::mpsc::UnboundedSender<tokio_tungstenite::tungstenite::Message>>>);
+
+fn websocket_sender(
+    sockets: &WebSockets,
+    connection_id: &str,
+) -> Result<tokio::sync::mpsc::UnboundedSender<tokio_tungstenite::tungstenite::Message>, String> {
+    let sockets = sockets.0.lock().map_err(|_| "WebSocket state is unavailable".to_string())?;
+    sockets
+        .get(connection_id)
+        .cloned()
+        .ok_or_else(|| format!("WebSocket connection not found: {}", connection_id))
+}
+
+fn decode_frame_payload(base64: Option<&str>) -> Result<Vec<u8>, String> {
+    match base64.filter(|data| !data.is_empty()) {
+        Some(data) => general_purpose::STANDARD
+            .decode(data)
+            .map_err(|e| format!("Invalid base64 payload: {}", e)),
+        None => Ok(Vec::new()),
+    }
+}
+
+// Push incoming frames as events until the connection ends
+async fn read_websocket<S>(app: &tauri::AppHandle, connection_id: &str, mut stream: S) -> WebSocketClosed
+where
+    S: futures::Stream<Item = Result<tokio_tungstenite::tungstenite::Message, tokio_tungstenite::tungstenite::Error>> + Unpin,
+{
+    use futures::StreamExt;
+    use tauri::Emitter;
+    use tokio_tungstenite::tungstenite::Message;
+
+    let mut closed = WebSocketClosed { connection_id: connection_id.to_string(), code: None, reason: None, error: None };
+    while let Some(frame) = stream.next().await {
+        let (kind, data) = match frame {
+            Ok(Message::Text(text)) => ("text", text.as_bytes().to_vec()),
+            Ok(Message::Binary(data)) => ("binary", data.to_vec()),
+            Ok(Message::Ping(data)) => ("ping", data.to_vec()),
+            Ok(Message::Pong(data)) => ("pong", data.to_vec()),
+            Ok(Message::Close(frame)) => {
+                if let Some(frame) = frame {
+                    closed.code = Some(u16::from(frame.code));
+                    closed.reason = Some(frame.reason.to_string()).filter(|reason| !reason.is_empty());
+                }
+                continue;
+            }
+            Ok(Message::Frame(_)) => continue,
+            Err(tokio_tungstenite::tungstenite::Error::ConnectionClosed) => break,
+            Err(e) => {
+                closed.error = Some(e.to_string());
+                break;
+            }
+        };
+        let _ = app.emit(
+            "websocket-message",
+            WebSocketMessage {
+                connection_id: connection_id.to_string(),
+                kind: kind.to_string(),
+                text: if kind == "text" { String::from_utf8(data.clone()).ok() } else { None },
+                data_base64: general_purpose::STANDARD.encode(&data),
+                size: data.len(),
+                timestamp: unix_now_ms(),
+            },
+        );
+    }
+    closed
+}
+
+// Open a WebSocket connection. Frames arrive as "websocket-message" events and the end
+// of the connection as "websocket-closed".
+#[tauri::command]
+pub async fn websocket_connect(
+    app: tauri::AppHandle,
+    sockets: tauri::State<'_, WebSockets>,
+    secrets: tauri::State<'_, SecretStore>,
+    request: WebSocketConnectRequest,
+) -> Result<WebSocketConnection, String> {
+    use futures::StreamExt;
+    use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+    use tokio_tungstenite::tungstenite::http::{HeaderName, HeaderValue};
+
+    let options = request.options.unwrap_or_default();
+    let mask = secrets.mask(&(&request.url, &request.headers));
+    let (url, headers) = secrets.resolve((request.url.clone(), request.headers.clone()))?;
+    let mut handshake = url
+        .as_str()
+        .into_client_request()
+        .map_err(|e| format!("Invalid WebSocket URL: {}", e))?;
+    for (name, value) in &headers {
+        let name = HeaderName::from_bytes(name.as_bytes()).map_err(|e| format!("Invalid header name {}: {}", name, e))?;
+        let value = HeaderValue::from_str(value).map_err(|e| format!("Invalid header value for {}: {}", name, e))?;
+        handshake.headers_mut().insert(name, value);
+    }
+    let protocols: Vec<&str> = request.protocols.iter().map(|p| p.trim()).filter(|p| !p.is_empty()).collect();
+    if !protocols.is_empty() {
+        let value = HeaderValue::from_str(&protocols.join(", ")).map_err(|e| format!("Invalid subprotocol: {}", e))?;
+        handshake.headers_mut().insert("Sec-WebSocket-Protocol", value);
+    }
+    // Secret values are reported as their {{secret:...}} references
+    let mut request_headers = header_map_to_hash_map(handshake.headers());
+    mask.apply_headers(&mut request_headers);
+
+    // The WebSocket upgrade is an HTTP/1.1 exchange
+    let mut tls = build_http_tls_config(&options)?;
+    tls.alpn_protocols = vec![b"http/1.1".to_vec()];
+    let connector = tokio_tungstenite::Connector::Rustls(std::sync::Arc::new(tls));
+
+    // Reserve the id before connecting; frames sent meanwhile go out once the handshake is done
+    let connection_id = request
+        .connection_id
+        .filter(|id| !id.is_empty())
+        .unwrap_or_else(|| format!("ws-{}", HTTP_STREAM_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed)));
+    let (sender, mut outgoing) = tokio::sync::mpsc::unbounded_channel();
+    {
+        let mut active = sockets.0.lock().map_err(|_| "WebSocket state is unavailable".to_string())?;
+        if active.contains_key(&connection_id) {
+            return Err(format!("Connection id already in use: {}", connection_id));
+        }
+        active.insert(connection_id.clone(), sender);
+    }
+
+    let connecting = tokio_tungstenite::connect_async_tls_with_config(handshake, None, true, Some(connector));
+    let describe = |e| match e {
+        tokio_tungstenite::tungstenite::Error::Http(response) => format!(
+            "WebSocket handshake rejected with HTTP {}{}",
+            response.status(),
+            response
+                .body()
+                .as_deref()
+                .map(|body| format!(": {}", String::from_utf8_lossy(body)))
+                .unwrap_or_default()
+        ),
+        e => format!("WebSocket connection failed: {}", e),
+    };
+    let describe = |e| mask.apply(&describe(e));
+    let connected = match options.timeout_ms.filter(|ms| *ms > 0) {
+        Some(ms) => match tokio::time::timeout(std::time::Duration::from_millis(ms), connecting).await {
+            Ok(result) => result.map_err(describe),
+            Err(_) => Err(format!("WebSocket handshake timed out after {} ms", ms)),
+        },
+        None => connecting.await.map_err(describe),
+    };
+    let (stream, response) = match connected {
+        Ok(connected) => connected,
+        Err(e) => {
+            if let Ok(mut active) = sockets.0.lock() {
+                active.remove(&connection_id);
+            }
+            return Err(e);
+        }
+    };
+
+    let mut response_headers = header_map_to_hash_map(response.headers());
+    mask.apply_headers(&mut response_headers);
+
+    let (mut sink, source) = stream.split();
+    tokio::spawn(async move {
+        use futures::SinkExt;
+        while let Some(message) = outgoing.recv().await {
+            if sink.send(message).await.is_err() {
+                break;
+            }
+        }
+    });
+    let id = connection_id.clone();
+    tokio::spawn(async move {
+        use tauri::{Emitter, Manager};
+
+        let closed = read_websocket(&app, &id, source).await;
+        // Dropping the sender also ends the writer task
+        if let Ok(mut active) = app.state::<WebSockets>().0.lock() {
+            active.remove(&id);
+        }
+        let _ = app.emit("websocket-closed", closed);
+    });
+
+    Ok(WebSocketConnection {
+        connection_id,
+        url: request.url,
+        status: response.status().as_u16(),
+        protocol: response
+            .headers()
+            .get("sec-websocket-protocol")
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string),
+        request_headers,
+        response_headers,
+    })
+}
+
+#[tauri::command]
+pub fn websocket_send(
+    sockets: tauri::State<'_, WebSockets>,
+    connection_id: String,
+    message: WebSocketOutgoing,
+) -> Result<(), String> {
+    use tokio_tungstenite::tungstenite::Message;
+
+    let frame = match message {
+        WebSocketOutgoing::Text { text } => Message::text(text),
+        WebSocketOutgoing::Binary { base64 } => Message::binary(decode_frame_payload(Some(&base64))?),
+        WebSocketOutgoing::Ping { base64 } => Message::Ping(decode_frame_payload(base64.as_deref())?.into()),
+        WebSocketOutgoing::Pong { base64 } => Message::Pong(decode_frame_payload(base64.as_deref())?.into()),
+    };
+    websocket_sender(&sockets, &connection_id)?
+        .send(frame)
+        .map_err(|_| "The WebSocket connection is closed".to_string())
+}
+
+// Start the closing handshake; "websocket-closed" follows once the server answers
+#[tauri::command]
+pub fn websocket_close(
+    sockets: tauri::State<'_, WebSockets>,
+    connection_id: String,
+    code: Option<u16>,
+    reason: Option<String>,
+) -> Result<(), String> {
+    use tokio_tungstenite::tungstenite::protocol::{frame::coding::CloseCode, CloseFrame};
+    use tokio_tungstenite::tungstenite::Message;
+
+    // 1005, 1006 and 1015 only describe a closure locally and must never be sent
+    let code = CloseCode::from(code.unwrap_or(1000));
+    if !code.is_allowed() {
+        return Err(format!("Close code {} cannot be sent; use 1000-1003, 1007-1014 or 3000-4999", code));
+    }
+    let reason = reason.unwrap_or_default();
+    // The close payload is limited to 125 bytes, two of which hold the code
+    if reason.len() > 123 {
+        return Err("The close reason is limited to 123 bytes".to_string());
+    }
+    let frame = CloseFrame { code, reason: reason.into() };
+    websocket_sender(&sockets, &connection_id)?
+        .send(Message::Close(Some(frame)))
+        .map_err(|_| "The WebSocket connection is closed".to_string())
+}
@@ -963,6 +963,7 @@ const translations = {
       responseBody: 'Response Body',
       noHeaders: 'No headers',
       noResponseBody: 'No response body',
+      binaryResponseBody: 'Binary response body',
       importCurl: 'Import cURL',
       exportCurl: 'Export cURL',
       importCurlHint: 'Paste your cURL command below and click Import to load the request configuration.',
@@ -3553,6 +3554,7 @@ const translations = {
       responseBody: '响应体',
       noHeaders: '无响应头',
       noResponseBody: '无响应体',
+      binaryResponseBody: '二进制响应体',
       importCurl: '导入 cURL',
       exportCurl: '导出 cURL',
       importCurlHint: '在下方粘贴您的 cURL 命令，点击导入以加载请求配置。',